@@ -0,0 +1,243 @@
+// Validation of destination addresses for the address format of the destination chain,
+// so an NFT is never locked towards an address the remote bridge can't deliver to.
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::{hash::hash, keccak};
+
+use crate::{AddressFormat, BridgeError};
+
+const C32_ALPHABET: &[u8] = b"0123456789ABCDEFGHJKMNPQRSTVWXYZ";
+// Mainnet and testnet, single signature and multisig address versions
+const STACKS_VERSIONS: [u8; 4] = [22, 20, 26, 21];
+const STACKS_CONTRACT_NAME_MAX: usize = 128;
+
+impl AddressFormat {
+    pub fn validate(self, address: &str) -> Result<()> {
+        let valid = match self {
+            AddressFormat::Evm => is_evm_address(address),
+            AddressFormat::Stacks => is_stacks_principal(address),
+            AddressFormat::Flow => is_flow_address(address),
+        };
+        require!(valid, BridgeError::InvalidDestinationAddress);
+
+        Ok(())
+    }
+}
+
+/// `0x` followed by 40 hex digits, mixed case must match the EIP-55 checksum
+fn is_evm_address(address: &str) -> bool {
+    let hex = match address.strip_prefix("0x") {
+        Some(hex) if hex.len() == 40 && hex.bytes().all(|c| c.is_ascii_hexdigit()) => hex,
+        _ => return false,
+    };
+
+    let has_lower = hex.bytes().any(|c| c.is_ascii_lowercase());
+    let has_upper = hex.bytes().any(|c| c.is_ascii_uppercase());
+    if !(has_lower && has_upper) {
+        return true;
+    }
+
+    let checksum = keccak::hash(hex.to_ascii_lowercase().as_bytes()).to_bytes();
+    hex.bytes().enumerate().all(|(i, c)| {
+        let nibble = (checksum[i / 2] >> (if i % 2 == 0 { 4 } else { 0 })) & 0x0f;
+        !c.is_ascii_alphabetic() || c.is_ascii_uppercase() == (nibble >= 8)
+    })
+}
+
+/// Standard principal `S<version><c32 hash160 + checksum>`, optionally followed by
+/// `.<contract-name>` for a contract principal
+fn is_stacks_principal(address: &str) -> bool {
+    let (principal, contract_name) = match address.split_once('.') {
+        Some((principal, name)) => (principal, Some(name)),
+        None => (address, None),
+    };
+
+    if let Some(name) = contract_name {
+        let mut chars = name.bytes();
+        let valid_name = name.len() <= STACKS_CONTRACT_NAME_MAX
+            && chars.next().is_some_and(|c| c.is_ascii_alphabetic())
+            && chars.all(|c| c.is_ascii_alphanumeric() || c == b'-' || c == b'_');
+        if !valid_name {
+            return false;
+        }
+    }
+
+    let principal = principal.as_bytes();
+    if principal.len() < 2 || principal[0] != b'S' {
+        return false;
+    }
+    let version = match c32_digit(principal[1]) {
+        Some(version) if STACKS_VERSIONS.contains(&version) => version,
+        _ => return false,
+    };
+
+    // hash160 followed by a 4 byte checksum
+    let data = match c32_decode(&principal[2..]) {
+        Some(data) if data.len() == 24 => data,
+        _ => return false,
+    };
+    let (hash160, checksum) = data.split_at(20);
+
+    let mut payload = vec![version];
+    payload.extend_from_slice(hash160);
+    let expected = hash(&hash(&payload).to_bytes()).to_bytes();
+
+    checksum == &expected[..4]
+}
+
+/// 8 byte address as 16 hex digits, with or without a `0x` prefix
+fn is_flow_address(address: &str) -> bool {
+    let hex = address.strip_prefix("0x").unwrap_or(address);
+    hex.len() == 16 && hex.bytes().all(|c| c.is_ascii_hexdigit())
+}
+
+fn c32_digit(c: u8) -> Option<u8> {
+    C32_ALPHABET.iter().position(|a| *a == c).map(|i| i as u8)
+}
+
+/// Same as `c32decode` of the Stacks c32check library, every leading `0` is a zero byte
+fn c32_decode(input: &[u8]) -> Option<Vec<u8>> {
+    // Large enough for a 24 byte payload
+    let mut value = [0u8; 32];
+    for c in input {
+        let digit = c32_digit(*c)?;
+        if value[0] >> 3 != 0 {
+            return None;
+        }
+        for i in 0..value.len() {
+            let next = value.get(i + 1).map_or(0, |b| b >> 3);
+            value[i] = (value[i] << 5) | next;
+        }
+        value[value.len() - 1] |= digit;
+    }
+
+    let leading_zeros = input.iter().take_while(|c| **c == b'0').count();
+    let significant = value
+        .iter()
+        .position(|b| *b != 0)
+        .map_or(&[][..], |i| &value[i..]);
+
+    let mut bytes = vec![0u8; leading_zeros];
+    bytes.extend_from_slice(significant);
+    Some(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn evm_checksum_vectors() {
+        // From EIP-55
+        assert!(is_evm_address("0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed"));
+        assert!(is_evm_address("0xfB6916095ca1df60bB79Ce92cE3Ea74c37c5d359"));
+        assert!(is_evm_address("0xdbF03B407c01E7cD3CBea99509d93f8DDDC8C6FB"));
+        assert!(is_evm_address("0xD1220A0cf47c7B9Be7A2E6BA89F429762e7b9aDb"));
+
+        // Single letter case flipped
+        assert!(!is_evm_address(
+            "0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAeD"
+        ));
+        assert!(!is_evm_address(
+            "0xFB6916095ca1df60bB79Ce92cE3Ea74c37c5d359"
+        ));
+    }
+
+    #[test]
+    fn evm_without_checksum() {
+        assert!(is_evm_address("0x5aaeb6053f3e94c9b9a09f33669435e7ef1beaed"));
+        assert!(is_evm_address("0x5AAEB6053F3E94C9B9A09F33669435E7EF1BEAED"));
+    }
+
+    #[test]
+    fn evm_malformed() {
+        assert!(!is_evm_address("5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed"));
+        assert!(!is_evm_address(
+            "0X5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed"
+        ));
+        assert!(!is_evm_address("0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeA"));
+        assert!(!is_evm_address(
+            "0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed00"
+        ));
+        assert!(!is_evm_address(
+            "0x5aaeb6053f3e94c9b9a09f33669435e7ef1beaeg"
+        ));
+        assert!(!is_evm_address(""));
+    }
+
+    #[test]
+    fn stacks_principals() {
+        assert!(is_stacks_principal(
+            "SP2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKNRV9EJ7"
+        ));
+        assert!(is_stacks_principal(
+            "ST2CY5V39NHDPWSXMW9QDT3HC3GD6Q6XX4CFRK9AG"
+        ));
+        assert!(is_stacks_principal(
+            "SP2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKNRV9EJ7.nft-bridge_v1"
+        ));
+    }
+
+    #[test]
+    fn stacks_bad_checksum() {
+        assert!(!is_stacks_principal(
+            "SP2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKNRV9EJ8"
+        ));
+        assert!(!is_stacks_principal(
+            "ST2CY5V39NHDPWSXMW9QDT3HC3GD6Q6XX4CFRK9AH"
+        ));
+    }
+
+    #[test]
+    fn stacks_malformed() {
+        // Unknown version, lowercase and a c32 character that doesn't exist
+        assert!(!is_stacks_principal(
+            "SA2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKNRV9EJ7"
+        ));
+        assert!(!is_stacks_principal(
+            "sp2j6zy48gv1ez5v2v5rb9mp66sw86pykknrv9ej7"
+        ));
+        assert!(!is_stacks_principal(
+            "SP2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKNRV9EJI"
+        ));
+        assert!(!is_stacks_principal(
+            "SP2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKNRV9E"
+        ));
+        assert!(!is_stacks_principal("S"));
+        assert!(!is_stacks_principal(""));
+
+        // Contract names start with a letter and only hold letters, digits, `-` and `_`
+        assert!(!is_stacks_principal(
+            "SP2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKNRV9EJ7.1bridge"
+        ));
+        assert!(!is_stacks_principal(
+            "SP2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKNRV9EJ7.nft.bridge"
+        ));
+        assert!(!is_stacks_principal(
+            "SP2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKNRV9EJ7."
+        ));
+        let long_name = format!(
+            "SP2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKNRV9EJ7.{}",
+            "a".repeat(STACKS_CONTRACT_NAME_MAX + 1)
+        );
+        assert!(!is_stacks_principal(&long_name));
+    }
+
+    #[test]
+    fn flow_addresses() {
+        assert!(is_flow_address("0xf8d6e0586b0a20c7"));
+        assert!(is_flow_address("1654653399040a61"));
+
+        assert!(!is_flow_address("0xf8d6e0586b0a20"));
+        assert!(!is_flow_address("0xf8d6e0586b0a20c700"));
+        assert!(!is_flow_address("0xf8d6e0586b0a20cg"));
+        assert!(!is_flow_address("0x"));
+    }
+
+    #[test]
+    fn validate_by_format() {
+        let stacks = "SP2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKNRV9EJ7";
+        assert!(AddressFormat::Stacks.validate(stacks).is_ok());
+        assert!(AddressFormat::Evm.validate(stacks).is_err());
+        assert!(AddressFormat::Flow.validate("0xf8d6e0586b0a20c7").is_ok());
+    }
+}
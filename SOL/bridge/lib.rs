@@ -1,290 +1,4168 @@
 use anchor_lang::prelude::*;
-use anchor_lang::solana_program::pubkey;
-use solana_program::program::invoke;
+use anchor_lang::solana_program::hash::hash;
+use anchor_lang::solana_program::instruction::{AccountMeta, Instruction};
+use anchor_lang::solana_program::{keccak, pubkey, secp256k1_recover::secp256k1_recover, sysvar};
+use solana_program::program::{invoke, invoke_signed};
 
 use anchor_spl::{
-    associated_token::AssociatedToken,
+    associated_token::{create_idempotent, AssociatedToken, Create},
     metadata::{
         create_master_edition_v3, create_metadata_accounts_v3,
         set_and_verify_sized_collection_item, sign_metadata, CreateMasterEditionV3,
         CreateMetadataAccountsV3, Metadata, SetAndVerifySizedCollectionItem, SignMetadata,
     },
-    token::{mint_to, Mint, MintTo, Token, TokenAccount, Transfer},
+    token::{
+        burn, initialize_account3, mint_to, Burn, InitializeAccount3, Mint, MintTo, Token,
+        TokenAccount, Transfer,
+    },
 };
 use mpl_token_metadata::accounts::{MasterEdition, Metadata as MetadataAccount};
 use mpl_token_metadata::types::{CollectionDetails, Creator, DataV2};
 
+mod address;
+#[path = "../common/chains.rs"]
+pub mod chains;
+use chains::ChainId;
+#[path = "../common/guardians.rs"]
+pub mod guardians;
+use guardians::{
+    guardian_batch_message, guardian_inbound_message, guardian_message, inbound_transfer_hash,
+    verify_guardian_signatures, GuardianSet, GUARDIAN_SET,
+};
+#[path = "../common/origin.rs"]
+pub mod origin;
+use origin::{load_collection_info, origin_collection_seed, origin_nft_seed, wrapped_nft_mint};
+
 declare_id!("ETWdEcjv3mCb9QzS9Kb6vqv7fi8c3hpLW7Jcsrz2hmEE");
 
 #[constant]
 pub const NFT_INFO: &str = "nft_info";
 pub const BRIDGE: &str = "bridge";
+pub const BRIDGE_CONFIG: &str = "bridge_config";
+pub const ROLE: &str = "role";
+pub const PROCESSED_TRANSFER: &str = "processed_transfer";
+pub const TREASURY: &str = "treasury";
+pub const CHAIN_CONFIG: &str = "chain_config";
+pub const COLLECTION_MAPPING: &str = "collection_mapping";
+pub const PENDING_UNLOCK: &str = "pending_unlock";
+pub const INBOUND_BATCH: &str = "inbound_batch";
+pub const MAX_INBOUND_BATCH_SIZE: u32 = 2048;
+pub const INBOUND_BATCH_DOMAIN: &str = "nft_bridge_inbound_batch_v1";
+// Wrapped NFTs are minted by the collection_creator program
+pub const COLLECTION_CREATOR_PROGRAM_ID: Pubkey =
+    pubkey!("AizEzdXgSms3KjkNEsBycmsmJD7LQa2wChwKHaKVXoix");
+// Seed of the wrapped collection mints of collection_creator
+pub const CREATOR_COLLECTION: &str = "Collection";
+pub const DEFAULT_GUARDIAN_SET_GRACE_PERIOD: i64 = 24 * 60 * 60;
+pub const UNLOCK_DOMAIN: &str = "nft_bridge_unlock_v1";
+pub const UNLOCK_BATCH_DOMAIN: &str = "nft_bridge_unlock_batch_v1";
+pub const BRIDGE_IN_DOMAIN: &str = "nft_bridge_in_v1";
+// Keeps a batch lock within the account limit of a transaction using a lookup table
+pub const DEFAULT_MAX_BATCH_SIZE: u8 = 8;
+// mint, metadata, sender token account, custody token account and nft info of each NFT
+pub const LOCK_BATCH_ACCOUNTS: usize = 5;
+// mint, metadata, custody token account, nft info, receiver token account and master edition
+// of each NFT
+pub const UNLOCK_BATCH_ACCOUNTS: usize = 6;
 
 #[program]
 pub mod anchor_nft_collection {
     use super::*;
 
-    pub fn initialize_bridge(_ctx: Context<InitializeBridge>) -> Result<()> {
+    pub fn initialize_bridge(
+        ctx: Context<InitializeBridge>,
+        operator: Pubkey,
+        treasury: Pubkey,
+    ) -> Result<()> {
+        let bridge_config = &mut ctx.accounts.bridge_config;
+
+        bridge_config.admin = ctx.accounts.signer.key();
+        bridge_config.operator = operator;
+        bridge_config.treasury = treasury;
+        bridge_config.guardian_set_grace_period = DEFAULT_GUARDIAN_SET_GRACE_PERIOD;
+        bridge_config.max_batch_size = DEFAULT_MAX_BATCH_SIZE;
+        bridge_config.bump = ctx.bumps.bridge_config;
+
+        ctx.accounts.treasury_pda.bump = ctx.bumps.treasury_pda;
+
+        Ok(())
+    }
+
+    pub fn set_admin(ctx: Context<UpdateBridgeConfig>, new_admin: Pubkey) -> Result<()> {
+        let bridge_config = &mut ctx.accounts.bridge_config;
+        msg!("Admin changed: {} -> {}", bridge_config.admin, new_admin);
+        bridge_config.admin = new_admin;
+
+        Ok(())
+    }
+
+    pub fn set_operator(ctx: Context<UpdateBridgeConfig>, new_operator: Pubkey) -> Result<()> {
+        let bridge_config = &mut ctx.accounts.bridge_config;
+        msg!(
+            "Operator changed: {} -> {}",
+            bridge_config.operator,
+            new_operator
+        );
+        bridge_config.operator = new_operator;
+
+        Ok(())
+    }
+
+    pub fn set_operator_public_key(
+        ctx: Context<UpdateBridgeConfig>,
+        operator_public_key: [u8; 64],
+    ) -> Result<()> {
+        ctx.accounts.bridge_config.operator_public_key = operator_public_key;
+
+        Ok(())
+    }
+
+    pub fn initialize_guardian_set(
+        ctx: Context<InitializeGuardianSet>,
+        keys: Vec<Pubkey>,
+        threshold: u8,
+    ) -> Result<()> {
+        GuardianSet::validate(&keys, threshold)?;
+
+        let guardian_set = &mut ctx.accounts.guardian_set;
+        guardian_set.index = 0;
+        guardian_set.keys = keys;
+        guardian_set.threshold = threshold;
+        guardian_set.activated_at = Clock::get()?.unix_timestamp;
+        guardian_set.expiration_time = 0;
+        guardian_set.bump = ctx.bumps.guardian_set;
+
+        ctx.accounts.bridge_config.guardian_set_index = 0;
+
+        Ok(())
+    }
+
+    pub fn set_guardian_set_grace_period(
+        ctx: Context<UpdateBridgeConfig>,
+        grace_period: i64,
+    ) -> Result<()> {
+        require!(grace_period >= 0, BridgeError::InvalidGuardianSet);
+        ctx.accounts.bridge_config.guardian_set_grace_period = grace_period;
+
+        Ok(())
+    }
+
+    // Stages the next guardian set, it is not accepted until activated
+    pub fn propose_guardian_set(
+        ctx: Context<ProposeGuardianSet>,
+        keys: Vec<Pubkey>,
+        threshold: u8,
+    ) -> Result<()> {
+        GuardianSet::validate(&keys, threshold)?;
+
+        let guardian_set = &mut ctx.accounts.guardian_set;
+        guardian_set.index = ctx.accounts.bridge_config.guardian_set_index + 1;
+        guardian_set.keys = keys;
+        guardian_set.threshold = threshold;
+        guardian_set.activated_at = 0;
+        guardian_set.expiration_time = 0;
+        guardian_set.bump = ctx.bumps.guardian_set;
+
+        Ok(())
+    }
+
+    pub fn activate_guardian_set(ctx: Context<ActivateGuardianSet>) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let bridge_config = &mut ctx.accounts.bridge_config;
+
+        // Only the set being replaced keeps a grace period, anything older stops now
+        if bridge_config.guardian_set_index > 0 {
+            let previous_guardian_set = ctx
+                .accounts
+                .previous_guardian_set
+                .as_mut()
+                .ok_or_else(|| error!(BridgeError::InvalidGuardianSet))?;
+            if previous_guardian_set.is_active(now) {
+                previous_guardian_set.expiration_time = now;
+            }
+        }
+
+        let current_guardian_set = &mut ctx.accounts.current_guardian_set;
+        current_guardian_set.expiration_time = now
+            .checked_add(bridge_config.guardian_set_grace_period)
+            .ok_or_else(|| error!(BridgeError::InvalidGuardianSet))?;
+
+        let new_guardian_set = &mut ctx.accounts.new_guardian_set;
+        new_guardian_set.activated_at = now;
+        bridge_config.guardian_set_index = new_guardian_set.index;
+
+        emit!(GuardianSetRotated {
+            previous_index: current_guardian_set.index,
+            previous_expiration_time: current_guardian_set.expiration_time,
+            new_index: new_guardian_set.index,
+            keys: new_guardian_set.keys.clone(),
+            threshold: new_guardian_set.threshold,
+        });
+
+        Ok(())
+    }
+
+    pub fn grant_role(ctx: Context<GrantRole>, role: Role, member: Pubkey) -> Result<()> {
+        let role_assignment = &mut ctx.accounts.role_assignment;
+
+        role_assignment.role = role;
+        role_assignment.member = member;
+        role_assignment.bump = ctx.bumps.role_assignment;
+
+        msg!("Role {:?} granted to {}", role, member);
+
+        Ok(())
+    }
+
+    pub fn revoke_role(_ctx: Context<RevokeRole>, role: Role, member: Pubkey) -> Result<()> {
+        msg!("Role {:?} revoked from {}", role, member);
+
+        Ok(())
+    }
+
+    pub fn set_service_fee(ctx: Context<ManageBridgeConfig>, service_fee: u64) -> Result<()> {
+        ctx.accounts.bridge_config.service_fee = service_fee;
+
+        Ok(())
+    }
+
+    pub fn set_max_batch_size(ctx: Context<ManageBridgeConfig>, max_batch_size: u8) -> Result<()> {
+        require!(max_batch_size > 0, BridgeError::InvalidNftLength);
+        ctx.accounts.bridge_config.max_batch_size = max_batch_size;
+
+        Ok(())
+    }
+
+    pub fn register_chain(
+        ctx: Context<RegisterChain>,
+        chain: ChainId,
+        bridge_manager: String,
+        address_format: AddressFormat,
+        ccip_chain_selector: u64,
+    ) -> Result<()> {
+        require!(!bridge_manager.is_empty(), BridgeError::ChainNotAllowed);
+
+        let chain_config = &mut ctx.accounts.chain_config;
+
+        chain_config.chain = chain;
+        chain_config.bridge_manager = bridge_manager;
+        chain_config.address_format = address_format;
+        chain_config.ccip_chain_selector = ccip_chain_selector;
+        chain_config.enabled = true;
+        chain_config.bump = ctx.bumps.chain_config;
+
+        Ok(())
+    }
+
+    pub fn set_chain_enabled(
+        ctx: Context<UpdateChainConfig>,
+        _chain: ChainId,
+        enabled: bool,
+    ) -> Result<()> {
+        ctx.accounts.chain_config.enabled = enabled;
+
+        Ok(())
+    }
+
+    pub fn set_chain_fee(
+        ctx: Context<UpdateChainConfig>,
+        _chain: ChainId,
+        base_fee: u64,
+        fee_per_nft: u64,
+    ) -> Result<()> {
+        let chain_config = &mut ctx.accounts.chain_config;
+
+        chain_config.base_fee = base_fee;
+        chain_config.fee_per_nft = fee_per_nft;
+
+        Ok(())
+    }
+
+    pub fn add_collection(
+        ctx: Context<AddCollection>,
+        collection: Pubkey,
+        dst_chain: ChainId,
+        dst_collection: String,
+    ) -> Result<()> {
+        require!(
+            !dst_collection.is_empty(),
+            BridgeError::CollectionNotAllowed
+        );
+
+        let collection_mapping = &mut ctx.accounts.collection_mapping;
+
+        collection_mapping.collection = collection;
+        collection_mapping.dst_chain = dst_chain;
+        collection_mapping.dst_collection = dst_collection;
+        collection_mapping.bump = ctx.bumps.collection_mapping;
+
+        Ok(())
+    }
+
+    // Collections with a non zero challenge period only unlock through propose_unlock
+    pub fn set_collection_challenge_period(
+        ctx: Context<UpdateCollectionMapping>,
+        _collection: Pubkey,
+        _dst_chain: ChainId,
+        challenge_period: u32,
+    ) -> Result<()> {
+        ctx.accounts.collection_mapping.challenge_period = challenge_period;
+
+        Ok(())
+    }
+
+    pub fn remove_collection_mapping(
+        _ctx: Context<RemoveCollectionMapping>,
+        _collection: Pubkey,
+        _dst_chain: ChainId,
+    ) -> Result<()> {
+        Ok(())
+    }
+
+    pub fn pause(ctx: Context<PauseBridge>) -> Result<()> {
+        ctx.accounts.bridge_config.paused = true;
+        msg!("Bridge paused by {}", ctx.accounts.signer.key());
+
+        Ok(())
+    }
+
+    pub fn unpause(ctx: Context<PauseBridge>) -> Result<()> {
+        ctx.accounts.bridge_config.paused = false;
+        msg!("Bridge unpaused by {}", ctx.accounts.signer.key());
+
+        Ok(())
+    }
+
+    pub fn set_chain_paused(ctx: Context<PauseChain>, chain: ChainId, paused: bool) -> Result<()> {
+        ctx.accounts.chain_config.paused = paused;
+        msg!("Chain {:?} paused: {}", chain, paused);
+
+        Ok(())
+    }
+
+    pub fn set_collection_paused(
+        ctx: Context<PauseCollection>,
+        collection: Pubkey,
+        dst_chain: ChainId,
+        paused: bool,
+    ) -> Result<()> {
+        ctx.accounts.collection_mapping.paused = paused;
+        msg!(
+            "Collection {} to {:?} paused: {}",
+            collection,
+            dst_chain,
+            paused
+        );
+
+        Ok(())
+    }
+
+    pub fn quote_fee(ctx: Context<QuoteFee>, _dst_chain: ChainId, nft_count: u64) -> Result<u64> {
+        require!(
+            nft_count > 0 && nft_count <= ctx.accounts.bridge_config.max_batch_size as u64,
+            BridgeError::InvalidNftLength
+        );
+
+        bridge_fee(
+            &ctx.accounts.bridge_config,
+            &ctx.accounts.chain_config,
+            nft_count,
+        )
+    }
+
+    pub fn withdraw_fees(ctx: Context<WithdrawFees>) -> Result<()> {
+        let treasury_pda = ctx.accounts.treasury_pda.to_account_info();
+
+        // Keep the treasury rent exempt, everything above that is collected fees
+        let rent_exempt = Rent::get()?.minimum_balance(treasury_pda.data_len());
+        let balance = treasury_pda.lamports().saturating_sub(rent_exempt);
+
+        if balance > 0 {
+            **treasury_pda.try_borrow_mut_lamports()? -= balance;
+            **ctx.accounts.treasury.try_borrow_mut_lamports()? += balance;
+        }
+
         Ok(())
     }
 
+    #[allow(clippy::too_many_arguments)]
     pub fn initialize_and_lock_nft_fee(
         ctx: Context<InitializeAndLockNftFee>,
-        origin_chain: String,
+        origin_chain: ChainId,
         origin_contract_address: String,
-        nft_id: u64,
+        nft_id: [u8; 32],
         amount: u64,
         coll_id: String,
         src_address: String,
-        dst_chain: String,
+        dst_chain: ChainId,
         dst_address: String,
     ) -> Result<()> {
+        // A malformed address would leave the NFT stuck in custody
+        ctx.accounts
+            .chain_config
+            .address_format
+            .validate(&dst_address)?;
+
+        // The fee always goes to the treasury PDA, the caller only confirms the amount
+        let fee = bridge_fee(&ctx.accounts.bridge_config, &ctx.accounts.chain_config, 1)?;
+        require!(amount == fee, BridgeError::InvalidFee);
+
+        // Only NFTs of a verified collection mapped towards dst_chain can be locked
+        verify_collection(
+            &ctx.accounts.metadata_account,
+            &ctx.accounts.collection_mapping.collection,
+        )?;
+
         // Initialization logic (creating PDAs, etc.)
         msg!("Initialization successful!");
 
-        // Token transfer logic (moving tokens into the bridge)
-        msg!("Token amount transfer in: 1 ");
+        let wrapped = is_wrapped_collection(
+            &ctx.accounts.collection_mapping.collection,
+            &ctx.accounts.collection_info_account,
+            origin_chain,
+            &origin_contract_address,
+        )?;
+        if wrapped {
+            // The original is held in custody on its origin chain, the wrapped copy is
+            // burned and minted again when it comes back
+            check_wrapped_mint(
+                &ctx.accounts.mint_of_token_being_sent.key(),
+                origin_chain,
+                &origin_contract_address,
+                &nft_id,
+            )?;
+            msg!("Token amount burned: 1 ");
 
-        // Transfer tokens from sender's token account to the bridge's NFT token account
-        let transfer_instruction = Transfer {
-            from: ctx.accounts.sender_token_account.to_account_info(),
-            to: ctx.accounts.nft_token_account.to_account_info(),
-            authority: ctx.accounts.signer.to_account_info(),
-        };
+            let burn_instruction = Burn {
+                mint: ctx.accounts.mint_of_token_being_sent.to_account_info(),
+                from: ctx.accounts.sender_token_account.to_account_info(),
+                authority: ctx.accounts.signer.to_account_info(),
+            };
 
-        let cpi_ctx = CpiContext::new(
-            ctx.accounts.token_program.to_account_info(),
-            transfer_instruction,
-        );
+            burn(
+                CpiContext::new(
+                    ctx.accounts.token_program.to_account_info(),
+                    burn_instruction,
+                ),
+                1,
+            )?;
+        } else {
+            // Token transfer logic (moving tokens into the bridge)
+            msg!("Token amount transfer in: 1 ");
+
+            // Transfer tokens from sender's token account to the bridge's NFT token account
+            let transfer_instruction = Transfer {
+                from: ctx.accounts.sender_token_account.to_account_info(),
+                to: required(&ctx.accounts.nft_token_account)?.to_account_info(),
+                authority: ctx.accounts.signer.to_account_info(),
+            };
 
-        // Perform the transfer
-        anchor_spl::token::transfer(cpi_ctx, 1)?;
+            let cpi_ctx = CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                transfer_instruction,
+            );
+
+            // Perform the transfer
+            anchor_spl::token::transfer(cpi_ctx, 1)?;
+        }
 
         let sol_transfer = anchor_lang::solana_program::system_instruction::transfer(
-            &ctx.accounts.from_pubkey.key,
-            &ctx.accounts.to_pubkey.key,
+            &ctx.accounts.signer.key(),
+            &ctx.accounts.treasury_pda.key(),
             amount,
         );
         invoke(
             &sol_transfer,
             &[
-                ctx.accounts.from_pubkey.clone(),
-                ctx.accounts.to_pubkey.clone(),
+                ctx.accounts.signer.to_account_info(),
+                ctx.accounts.treasury_pda.to_account_info(),
                 ctx.accounts.system_program.to_account_info(),
             ],
         )?;
 
         let nft_info_account = &mut ctx.accounts.nft_info_account;
         nft_info_account.mint_address = ctx.accounts.mint_of_token_being_sent.key();
+        nft_info_account.origin_chain = origin_chain;
+
+        // Same as the Stacks bridge: report the current nonce, then bump it
+        let nonce = nft_info_account.nonce;
+        nft_info_account.nonce = nonce + 1;
 
-        msg!("NFT ID: {}", nft_id);
-        msg!("Collection ID: {}", coll_id);
-        msg!("Source Address: {}", src_address);
-        msg!("Destination Chain: {}", dst_chain);
-        msg!("Destination Address: {}", dst_address);
+        emit!(NftLocked {
+            owner: ctx.accounts.signer.key(),
+            mint: ctx.accounts.mint_of_token_being_sent.key(),
+            origin_chain,
+            origin_contract_address,
+            nft_id,
+            coll_id,
+            src_address,
+            dst_chain,
+            dst_address,
+            fee: amount,
+            nonce,
+        });
 
         Ok(())
     }
 
-    pub fn unlock_nft(
-        ctx: Context<UnlockNft>,
-        origin_chain: String,
+    /// Locks several NFTs of one collection for a single fee, like EVM `lockNFT` with
+    /// `tokenIds[]`. For every id `remaining_accounts` holds, in order, the mint (writable
+    /// for wrapped NFTs, which are burned), its metadata, the sender token account, the
+    /// custody token account (unused for wrapped NFTs) and the nft info.
+    #[allow(clippy::too_many_arguments)]
+    pub fn lock_nfts_batch<'info>(
+        ctx: Context<'_, '_, '_, 'info, LockNftsBatch<'info>>,
+        origin_chain: ChainId,
         origin_contract_address: String,
-        nft_id: u64,
+        nft_ids: Vec<[u8; 32]>,
+        amount: u64,
         coll_id: String,
-        src_chain: String,
         src_address: String,
+        dst_chain: ChainId,
         dst_address: String,
-        bridge_txid: String,
     ) -> Result<()> {
-        msg!("Token amount transfer out: 1 ");
+        require!(
+            !nft_ids.is_empty()
+                && nft_ids.len() <= ctx.accounts.bridge_config.max_batch_size as usize
+                && ctx.remaining_accounts.len() == nft_ids.len() * LOCK_BATCH_ACCOUNTS,
+            BridgeError::InvalidNftLength
+        );
 
-        // Below is the actual instruction that we are going to send to the Token program.
-        let transfer_instruction = Transfer {
-            from: ctx.accounts.nft_token_account.to_account_info(),
-            to: ctx.accounts.receiver_token_account.to_account_info(),
-            authority: ctx.accounts.bridge_pda.to_account_info(),
-        };
+        ctx.accounts
+            .chain_config
+            .address_format
+            .validate(&dst_address)?;
 
-        let bump = ctx.bumps.bridge_pda;
-        let seeds = &[BRIDGE.as_bytes().as_ref(), &[bump]];
-        let signer = &[&seeds[..]];
+        let fee = bridge_fee(
+            &ctx.accounts.bridge_config,
+            &ctx.accounts.chain_config,
+            nft_ids.len() as u64,
+        )?;
+        require!(amount == fee, BridgeError::InvalidFee);
 
-        let cpi_ctx = CpiContext::new_with_signer(
-            ctx.accounts.token_program.to_account_info(),
-            transfer_instruction,
-            signer,
-        );
+        let wrapped = is_wrapped_collection(
+            &ctx.accounts.collection_mapping.collection,
+            &ctx.accounts.collection_info_account,
+            origin_chain,
+            &origin_contract_address,
+        )?;
+
+        let signer = ctx.accounts.signer.to_account_info();
+        let bridge_pda = ctx.accounts.bridge_pda.to_account_info();
+        let system_program = ctx.accounts.system_program.to_account_info();
+        let token_program = ctx.accounts.token_program.to_account_info();
+
+        let mut mints = Vec::with_capacity(nft_ids.len());
+        let mut nonces = Vec::with_capacity(nft_ids.len());
+        for (nft_id, accounts) in nft_ids
+            .iter()
+            .zip(ctx.remaining_accounts.chunks(LOCK_BATCH_ACCOUNTS))
+        {
+            let [mint, metadata_account, sender_token_account, nft_token_account, nft_info_account] =
+                accounts
+            else {
+                return err!(BridgeError::InvalidNftLength);
+            };
+
+            require_keys_eq!(
+                metadata_account.key(),
+                MetadataAccount::find_pda(&mint.key()).0,
+                BridgeError::CollectionNotAllowed
+            );
+            verify_collection(
+                metadata_account,
+                &ctx.accounts.collection_mapping.collection,
+            )?;
 
-        anchor_spl::token::transfer(cpi_ctx, 1)?;
+            require_keys_eq!(
+                *sender_token_account.owner,
+                Token::id(),
+                BridgeError::InvalidNftOwner
+            );
+            let sender =
+                TokenAccount::try_deserialize(&mut &sender_token_account.try_borrow_data()?[..])?;
+            require!(
+                sender.owner == signer.key() && sender.mint == mint.key() && sender.amount == 1,
+                BridgeError::InvalidNftOwner
+            );
+
+            let nft_seed = origin_nft_seed(origin_chain, &origin_contract_address, nft_id);
+
+            // Wrapped NFTs are burned, like in a single lock, and never touch custody
+            if wrapped {
+                check_wrapped_mint(&mint.key(), origin_chain, &origin_contract_address, nft_id)?;
+                burn(
+                    CpiContext::new(
+                        token_program.clone(),
+                        Burn {
+                            mint: mint.clone(),
+                            from: sender_token_account.clone(),
+                            authority: signer.clone(),
+                        },
+                    ),
+                    1,
+                )?;
+            } else {
+                // Same custody account as a single lock of this NFT
+                let created = init_pda_account(
+                    &signer,
+                    nft_token_account,
+                    &system_program,
+                    &[&nft_seed],
+                    TokenAccount::LEN,
+                    &Token::id(),
+                )?;
+                if created {
+                    initialize_account3(CpiContext::new(
+                        token_program.clone(),
+                        InitializeAccount3 {
+                            account: nft_token_account.clone(),
+                            mint: mint.clone(),
+                            authority: bridge_pda.clone(),
+                        },
+                    ))?;
+                } else {
+                    let custody = TokenAccount::try_deserialize(
+                        &mut &nft_token_account.try_borrow_data()?[..],
+                    )?;
+                    require_keys_eq!(custody.mint, mint.key(), ErrorCode::ConstraintTokenMint);
+                    require_keys_eq!(
+                        custody.owner,
+                        bridge_pda.key(),
+                        ErrorCode::ConstraintTokenOwner
+                    );
+                }
+
+                anchor_spl::token::transfer(
+                    CpiContext::new(
+                        token_program.clone(),
+                        Transfer {
+                            from: sender_token_account.clone(),
+                            to: nft_token_account.clone(),
+                            authority: signer.clone(),
+                        },
+                    ),
+                    1,
+                )?;
+            }
+
+            let created = init_pda_account(
+                &signer,
+                nft_info_account,
+                &system_program,
+                &[NFT_INFO.as_bytes(), &nft_seed],
+                8 + std::mem::size_of::<NftInfoInBridge>(),
+                ctx.program_id,
+            )?;
+            let mut nft_info = if created {
+                NftInfoInBridge {
+                    mint_address: mint.key(),
+                    nonce: 0,
+                    origin_chain,
+                }
+            } else {
+                NftInfoInBridge::try_deserialize(&mut &nft_info_account.try_borrow_data()?[..])?
+            };
+            nft_info.mint_address = mint.key();
+            nft_info.origin_chain = origin_chain;
+
+            // Same as a single lock: report the current nonce, then bump it
+            nonces.push(nft_info.nonce);
+            nft_info.nonce += 1;
+            nft_info.try_serialize(&mut &mut nft_info_account.try_borrow_mut_data()?[..])?;
+
+            mints.push(mint.key());
+        }
+
+        let sol_transfer = anchor_lang::solana_program::system_instruction::transfer(
+            &signer.key(),
+            &ctx.accounts.treasury_pda.key(),
+            amount,
+        );
+        invoke(
+            &sol_transfer,
+            &[
+                signer.clone(),
+                ctx.accounts.treasury_pda.to_account_info(),
+                system_program,
+            ],
+        )?;
 
-        msg!("NFT ID: {}", nft_id);
-        msg!("Collection ID: {}", coll_id);
-        msg!("Source Chain: {}", src_chain);
-        msg!("Source Address: {}", src_address);
-        msg!("Destination Address: {}", dst_address);
-        msg!("Bridge TxId: {}", bridge_txid);
+        emit!(NftsLocked {
+            owner: signer.key(),
+            mints,
+            origin_chain,
+            origin_contract_address,
+            nft_ids,
+            coll_id,
+            src_address,
+            dst_chain,
+            dst_address,
+            fee: amount,
+            nonces,
+        });
 
         Ok(())
     }
 
-
-    pub fn store_nft_info_in_bridge(
-        ctx: Context<StoreNftInfoInBridge>,
-        origin_chain: String,            // origin_chain passed from client
-        origin_contract_address: String, // origin_contract_address passed from client
-        mint_address: Pubkey,
-        nft_id: u64,
+    #[allow(clippy::too_many_arguments)]
+    pub fn unlock_nft(
+        ctx: Context<UnlockNft>,
+        origin_chain: ChainId,
+        origin_contract_address: String,
+        nft_id: [u8; 32],
+        coll_id: String,
+        src_chain: ChainId,
+        src_address: String,
+        dst_address: String,
+        bridge_txid: String,
+        nonce: u64,
+        attestation: Attestation,
     ) -> Result<()> {
+        require!(
+            ctx.accounts.collection_mapping.challenge_period == 0,
+            BridgeError::ChallengeWindowRequired
+        );
+        verify_collection(
+            &ctx.accounts.metadata_account,
+            &ctx.accounts.collection_mapping.collection,
+        )?;
+
+        // The attestation is what authorises the unlock, not the relayer
+        verify_attestation(
+            &attestation,
+            &ctx.accounts.bridge_config,
+            ctx.accounts.guardian_set.as_ref(),
+            ctx.accounts.instructions.as_ref(),
+            src_chain,
+            &bridge_txid,
+            origin_chain,
+            &origin_contract_address,
+            nft_id,
+            nonce,
+            &ctx.accounts.receiver.key(),
+            &ctx.accounts.mint_of_token_being_sent.key(),
+        )?;
+
+        // Out of custody, or minted again if it was burned when it left Solana
+        let minted = release_nft(
+            &ctx.accounts.custody_accounts(),
+            || ctx.accounts.creator_accounts(),
+            &ctx.accounts.collection_mapping.collection,
+            &ctx.accounts.collection_info_account,
+            ctx.bumps.bridge_pda,
+            origin_chain,
+            &origin_contract_address,
+        )?;
+        if minted {
+            msg!("Token amount minted: 1 ");
+        } else {
+            msg!("Token amount transfer out: 1 ");
+        }
+
+        let processed_transfer = &mut ctx.accounts.processed_transfer;
+        processed_transfer.processed = true;
+        processed_transfer.mint = ctx.accounts.mint_of_token_being_sent.key();
+        processed_transfer.receiver = ctx.accounts.receiver_token_account.owner;
+        processed_transfer.processed_at = Clock::get()?.unix_timestamp;
+        processed_transfer.bump = ctx.bumps.processed_transfer;
+
         let nft_info_account = &mut ctx.accounts.nft_info_account;
+        nft_info_account.nonce = nonce + 1;
 
-        nft_info_account.mint_address = mint_address;
+        emit!(NftUnlocked {
+            src_chain,
+            src_address,
+            receiver: ctx.accounts.receiver.key(),
+            mint: ctx.accounts.mint_of_token_being_sent.key(),
+            origin_chain,
+            origin_contract_address,
+            nft_id,
+            coll_id,
+            dst_address,
+            bridge_txid,
+            nonce,
+        });
 
         Ok(())
     }
-}
-
-#[derive(Accounts)]
-pub struct InitializeBridge<'info> {
-    // Derived PDAs
-    #[account(
-        init_if_needed,
-        payer = signer,
-        seeds=[BRIDGE.as_bytes()],
-        bump,
-        space = 8 + 8
-    )]
-    bridge_pda: AccountInfo<'info>,
 
-    #[account(mut,address=pubkey!("7QHySLfCkeSBUGxtqM3WdeKv9X4YUXMeTn3gr5fRPCGU"))]
-    signer: Signer<'info>,
-    system_program: Program<'info, System>,
-    token_program: Program<'info, Token>,
-    rent: Sysvar<'info, Rent>,
-}
-
-#[derive(Accounts)]
-#[instruction(
-        origin_chain: String,
+    /// Releases several NFTs of an inbound multi token transfer (EVM `NFTLocked` with
+    /// `tokenIds[]`) to one receiver. For every id `remaining_accounts` holds, in order,
+    /// the mint (writable for wrapped NFTs, which are minted again), its metadata, the
+    /// custody token account, the nft info, the receiver token account and the master
+    /// edition. Any failing NFT reverts the whole batch.
+    #[allow(clippy::too_many_arguments)]
+    pub fn unlock_nfts_batch<'info>(
+        ctx: Context<'_, '_, '_, 'info, UnlockNftsBatch<'info>>,
+        origin_chain: ChainId,
         origin_contract_address: String,
-        nft_id: u64,
-        amount: u64,
+        nft_ids: Vec<[u8; 32]>,
+        nonces: Vec<u64>,
         coll_id: String,
+        src_chain: ChainId,
         src_address: String,
-        dst_chain: String,
-        dst_address: String,)]
-pub struct InitializeAndLockNftFee<'info> {
-    // Derived PDAs and accounts for initialization
-    #[account(
-        mut,
-        seeds=[BRIDGE.as_bytes()],
-        bump
-    )]
-    pub bridge_pda: AccountInfo<'info>, // `mut` is needed because the PDA will be initialized
+        dst_address: String,
+        bridge_txid: String,
+        attestation: Attestation,
+    ) -> Result<()> {
+        require!(
+            !nft_ids.is_empty()
+                && nft_ids.len() <= ctx.accounts.bridge_config.max_batch_size as usize
+                && nonces.len() == nft_ids.len()
+                && ctx.remaining_accounts.len() == nft_ids.len() * UNLOCK_BATCH_ACCOUNTS,
+            BridgeError::InvalidNftLength
+        );
+        require!(
+            ctx.accounts.collection_mapping.challenge_period == 0,
+            BridgeError::ChallengeWindowRequired
+        );
 
-    pub mint_of_token_being_sent: Account<'info, Mint>,
+        let mints: Vec<Pubkey> = ctx
+            .remaining_accounts
+            .chunks(UNLOCK_BATCH_ACCOUNTS)
+            .map(|accounts| accounts[0].key())
+            .collect();
 
-    #[account(
-        init_if_needed,
-        payer = signer,
-        seeds = [&nft_id.to_le_bytes(), origin_chain.as_bytes(), origin_contract_address.as_bytes()],
-        token::mint = mint_of_token_being_sent,
-        token::authority = bridge_pda,
-        bump
-    )]
-    pub nft_token_account: Account<'info, TokenAccount>, // `mut` is needed because the account will be initialized
+        // The attestation is what authorises the unlock, not the relayer
+        verify_batch_attestation(
+            &attestation,
+            &ctx.accounts.bridge_config,
+            ctx.accounts.guardian_set.as_ref(),
+            ctx.accounts.instructions.as_ref(),
+            src_chain,
+            &bridge_txid,
+            origin_chain,
+            &origin_contract_address,
+            &nft_ids,
+            &nonces,
+            &ctx.accounts.receiver.key(),
+            &mints,
+        )?;
 
-    #[account(
-        init_if_needed,
-        seeds = [NFT_INFO.as_bytes(),&nft_id.to_le_bytes(), origin_chain.as_bytes(), origin_contract_address.as_bytes()],
-        bump,
-        payer = signer,
-        space = 8 + std::mem::size_of::<NftInfoInBridge>(),
-    )]
-    pub nft_info_account: Box<Account<'info, NftInfoInBridge>>,
+        for ((nft_id, nonce), accounts) in nft_ids
+            .iter()
+            .zip(&nonces)
+            .zip(ctx.remaining_accounts.chunks(UNLOCK_BATCH_ACCOUNTS))
+        {
+            let [mint, metadata_account, nft_token_account, nft_info_account, receiver_token_account, master_edition] =
+                accounts
+            else {
+                return err!(BridgeError::InvalidNftLength);
+            };
 
-    #[account(mut)]
-    pub sender_token_account: Account<'info, TokenAccount>, // `mut` is needed because the account will be used for transfer
+            require_keys_eq!(
+                metadata_account.key(),
+                MetadataAccount::find_pda(&mint.key()).0,
+                BridgeError::CollectionNotAllowed
+            );
+            verify_collection(
+                metadata_account,
+                &ctx.accounts.collection_mapping.collection,
+            )?;
 
-    #[account(mut)]
-    pub to_pubkey: AccountInfo<'info>,
+            let nft_seed = origin_nft_seed(origin_chain, &origin_contract_address, nft_id);
 
-    #[account(mut)]
-    pub from_pubkey: AccountInfo<'info>,
+            require_keys_eq!(
+                nft_token_account.key(),
+                Pubkey::find_program_address(&[&nft_seed], ctx.program_id).0,
+                ErrorCode::ConstraintSeeds
+            );
 
-    #[account(mut)]
-    pub signer: Signer<'info>, // `mut` is needed because the signer is part of the transaction
+            require_keys_eq!(
+                nft_info_account.key(),
+                Pubkey::find_program_address(&[NFT_INFO.as_bytes(), &nft_seed], ctx.program_id).0,
+                ErrorCode::ConstraintSeeds
+            );
+            require_keys_eq!(
+                *nft_info_account.owner,
+                *ctx.program_id,
+                ErrorCode::ConstraintOwner
+            );
+            let mut nft_info =
+                NftInfoInBridge::try_deserialize(&mut &nft_info_account.try_borrow_data()?[..])?;
+            require!(nft_info.nonce == *nonce, BridgeError::InvalidNonce);
 
-    pub system_program: Program<'info, System>,
-    pub token_program: Program<'info, Token>,
-    pub rent: Sysvar<'info, Rent>,
-}
+            // Same as a single unlock, burned wrapped NFTs are minted again
+            release_nft(
+                &ctx.accounts.custody_accounts(
+                    mint,
+                    metadata_account,
+                    nft_token_account,
+                    receiver_token_account,
+                ),
+                || {
+                    ctx.accounts.creator_accounts(
+                        mint,
+                        metadata_account,
+                        master_edition,
+                        receiver_token_account,
+                    )
+                },
+                &ctx.accounts.collection_mapping.collection,
+                &ctx.accounts.collection_info_account,
+                ctx.bumps.bridge_pda,
+                origin_chain,
+                &origin_contract_address,
+            )?;
 
-#[derive(Accounts)]
-#[instruction(origin_chain: String,
+            nft_info.nonce = nonce + 1;
+            nft_info.try_serialize(&mut &mut nft_info_account.try_borrow_mut_data()?[..])?;
+        }
+
+        // One txid covers the whole set
+        let processed_transfer = &mut ctx.accounts.processed_transfer;
+        processed_transfer.processed = true;
+        processed_transfer.mint = mints[0];
+        processed_transfer.mints = mints.clone();
+        processed_transfer.receiver = ctx.accounts.receiver.key();
+        processed_transfer.processed_at = Clock::get()?.unix_timestamp;
+        processed_transfer.bump = ctx.bumps.processed_transfer;
+
+        emit!(NftsUnlocked {
+            src_chain,
+            src_address,
+            receiver: ctx.accounts.receiver.key(),
+            mints,
+            origin_chain,
+            origin_contract_address,
+            nft_ids,
+            coll_id,
+            dst_address,
+            bridge_txid,
+            nonces,
+        });
+
+        Ok(())
+    }
+
+    // First phase of an optimistic unlock, the NFT stays in custody until execute_unlock
+    #[allow(clippy::too_many_arguments)]
+    pub fn propose_unlock(
+        ctx: Context<ProposeUnlock>,
+        origin_chain: ChainId,
         origin_contract_address: String,
-        nft_id: u64,
+        nft_id: [u8; 32],
         coll_id: String,
-        src_chain: String,
+        src_chain: ChainId,
         src_address: String,
         dst_address: String,
-        bridge_txid: String,)]
-pub struct UnlockNft<'info> {
+        bridge_txid: String,
+        nonce: u64,
+        attestation: Attestation,
+    ) -> Result<()> {
+        verify_collection(
+            &ctx.accounts.metadata_account,
+            &ctx.accounts.collection_mapping.collection,
+        )?;
+        verify_attestation(
+            &attestation,
+            &ctx.accounts.bridge_config,
+            ctx.accounts.guardian_set.as_ref(),
+            ctx.accounts.instructions.as_ref(),
+            src_chain,
+            &bridge_txid,
+            origin_chain,
+            &origin_contract_address,
+            nft_id,
+            nonce,
+            &ctx.accounts.receiver.key(),
+            &ctx.accounts.mint_of_token_being_sent.key(),
+        )?;
+
+        let now = Clock::get()?.unix_timestamp;
+        let executable_after = now + ctx.accounts.collection_mapping.challenge_period as i64;
+
+        let processed_transfer = &mut ctx.accounts.processed_transfer;
+        processed_transfer.processed = true;
+        processed_transfer.mint = ctx.accounts.mint_of_token_being_sent.key();
+        processed_transfer.receiver = ctx.accounts.receiver.key();
+        processed_transfer.processed_at = now;
+        processed_transfer.bump = ctx.bumps.processed_transfer;
+
+        let pending_unlock = &mut ctx.accounts.pending_unlock;
+        pending_unlock.proposer = ctx.accounts.signer.key();
+        pending_unlock.receiver = ctx.accounts.receiver.key();
+        pending_unlock.mint = ctx.accounts.mint_of_token_being_sent.key();
+        pending_unlock.collection = ctx.accounts.collection_mapping.collection;
+        pending_unlock.origin_chain = origin_chain;
+        pending_unlock.origin_contract_address = origin_contract_address;
+        pending_unlock.nft_id = nft_id;
+        pending_unlock.coll_id = coll_id;
+        pending_unlock.src_chain = src_chain;
+        pending_unlock.src_address = src_address;
+        pending_unlock.dst_address = dst_address;
+        pending_unlock.bridge_txid = bridge_txid;
+        pending_unlock.nonce = nonce;
+        pending_unlock.executable_after = executable_after;
+        pending_unlock.bump = ctx.bumps.pending_unlock;
+
+        emit!(UnlockProposed {
+            src_chain: pending_unlock.src_chain,
+            bridge_txid: pending_unlock.bridge_txid.clone(),
+            receiver: pending_unlock.receiver,
+            mint: pending_unlock.mint,
+            nft_id,
+            executable_after,
+        });
+
+        Ok(())
+    }
+
+    // Anyone can finish a proposed unlock once its challenge window has passed
+    pub fn execute_unlock(ctx: Context<ExecuteUnlock>) -> Result<()> {
+        let pending_unlock = &ctx.accounts.pending_unlock;
+        require!(
+            Clock::get()?.unix_timestamp >= pending_unlock.executable_after,
+            BridgeError::ChallengeWindowOpen
+        );
+
+        release_nft(
+            &ctx.accounts.custody_accounts(),
+            || ctx.accounts.creator_accounts(),
+            &ctx.accounts.collection_mapping.collection,
+            &ctx.accounts.collection_info_account,
+            ctx.bumps.bridge_pda,
+            pending_unlock.origin_chain,
+            &pending_unlock.origin_contract_address,
+        )?;
+
+        ctx.accounts.nft_info_account.nonce = pending_unlock.nonce + 1;
+
+        emit!(NftUnlocked {
+            src_chain: pending_unlock.src_chain,
+            src_address: pending_unlock.src_address.clone(),
+            receiver: pending_unlock.receiver,
+            mint: pending_unlock.mint,
+            origin_chain: pending_unlock.origin_chain,
+            origin_contract_address: pending_unlock.origin_contract_address.clone(),
+            nft_id: pending_unlock.nft_id,
+            coll_id: pending_unlock.coll_id.clone(),
+            dst_address: pending_unlock.dst_address.clone(),
+            bridge_txid: pending_unlock.bridge_txid.clone(),
+            nonce: pending_unlock.nonce,
+        });
+
+        Ok(())
+    }
+
+    // Drops a proposed unlock, its bridge txid stays consumed until the admin clears the veto
+    pub fn veto_unlock(ctx: Context<VetoUnlock>) -> Result<()> {
+        let pending_unlock = &ctx.accounts.pending_unlock;
+        require!(
+            Clock::get()?.unix_timestamp < pending_unlock.executable_after,
+            BridgeError::ChallengeWindowClosed
+        );
+
+        ctx.accounts.processed_transfer.vetoed = true;
+
+        emit!(UnlockVetoed {
+            src_chain: pending_unlock.src_chain,
+            bridge_txid: pending_unlock.bridge_txid.clone(),
+            receiver: pending_unlock.receiver,
+            mint: pending_unlock.mint,
+            watcher: ctx.accounts.signer.key(),
+        });
+
+        Ok(())
+    }
+
+    // Lets a vetoed bridge txid be proposed again, once the transfer has been re-checked
+    pub fn clear_vetoed_transfer(
+        ctx: Context<ClearVetoedTransfer>,
+        src_chain: ChainId,
+        bridge_txid: String,
+    ) -> Result<()> {
+        let processed_transfer = &mut ctx.accounts.processed_transfer;
+        processed_transfer.processed = false;
+        processed_transfer.vetoed = false;
+        msg!("Veto of {} from {:?} cleared", bridge_txid, src_chain);
+
+        Ok(())
+    }
+
+    // Posts the Merkle root of a batch of inbound transfers, signed by the operator
+    pub fn post_inbound_batch(
+        ctx: Context<PostInboundBatch>,
+        src_chain: ChainId,
+        batch_id: u64,
+        merkle_root: [u8; 32],
+        leaf_count: u32,
+        signature: [u8; 65],
+    ) -> Result<()> {
+        require!(
+            leaf_count > 0 && leaf_count <= MAX_INBOUND_BATCH_SIZE,
+            BridgeError::InvalidInboundBatch
+        );
+
+        let message_hash =
+            inbound_batch_message_hash(src_chain, batch_id, &merkle_root, leaf_count);
+        verify_operator_signature(&ctx.accounts.bridge_config, &message_hash, &signature)?;
+
+        let inbound_batch = &mut ctx.accounts.inbound_batch;
+        inbound_batch.src_chain = src_chain;
+        inbound_batch.batch_id = batch_id;
+        inbound_batch.merkle_root = merkle_root;
+        inbound_batch.leaf_count = leaf_count;
+        inbound_batch.claimed = vec![0; (leaf_count as usize).div_ceil(8)];
+        inbound_batch.bump = ctx.bumps.inbound_batch;
+
+        emit!(InboundBatchPosted {
+            src_chain,
+            batch_id,
+            merkle_root,
+            leaf_count,
+        });
+
+        Ok(())
+    }
+
+    // Claims one transfer of an inbound batch, delivered the same way as bridge_in
+    pub fn claim_with_proof(
+        ctx: Context<ClaimWithProof>,
+        src_chain: ChainId,
+        batch_id: u64,
+        index: u32,
+        transfer: InboundTransfer,
+        proof: Vec<[u8; 32]>,
+    ) -> Result<()> {
+        // A single operator signature posts the batch, it can't skip the challenge window
+        require!(
+            ctx.accounts.collection_mapping.challenge_period == 0,
+            BridgeError::ChallengeWindowRequired
+        );
+
+        let inbound_batch = &mut ctx.accounts.inbound_batch;
+        require!(
+            index < inbound_batch.leaf_count,
+            BridgeError::InvalidInboundBatch
+        );
+
+        let (byte, bit) = ((index / 8) as usize, 1u8 << (index % 8));
+        require!(
+            inbound_batch.claimed[byte] & bit == 0,
+            BridgeError::TransferAlreadyProcessed
+        );
+        require!(
+            verify_merkle_proof(
+                inbound_leaf_hash(index, &transfer),
+                &proof,
+                &inbound_batch.merkle_root
+            ),
+            BridgeError::InvalidMerkleProof
+        );
+        inbound_batch.claimed[byte] |= bit;
+
+        // Once known, an NFT always comes back as the same mint
+        let nft_mint = ctx.accounts.nft_mint.key();
+        let known_mint = ctx.accounts.nft_info_account.mint_address;
+        if known_mint != Pubkey::default() {
+            require_keys_eq!(nft_mint, known_mint, ErrorCode::ConstraintAddress);
+        }
+
+        // The leaf has no say in the path, an NFT held in custody is never minted
+        deliver_inbound(
+            &ctx.accounts.custody_accounts(),
+            || ctx.accounts.creator_accounts(),
+            &ctx.accounts.collection_mapping.collection,
+            &ctx.accounts.collection_info_account,
+            known_mint,
+            ctx.bumps.bridge_pda,
+            src_chain,
+            &transfer,
+        )?;
+
+        let nft_info_account = &mut ctx.accounts.nft_info_account;
+        nft_info_account.mint_address = nft_mint;
+        nft_info_account.origin_chain = transfer.origin_chain;
+        nft_info_account.nonce = transfer.nonce + 1;
+
+        let processed_transfer = &mut ctx.accounts.processed_transfer;
+        processed_transfer.processed = true;
+        processed_transfer.mint = nft_mint;
+        processed_transfer.receiver = transfer.receiver;
+        processed_transfer.processed_at = Clock::get()?.unix_timestamp;
+        processed_transfer.bump = ctx.bumps.processed_transfer;
+
+        emit!(InboundTransferClaimed {
+            src_chain,
+            batch_id,
+            index,
+            receiver: transfer.receiver,
+            mint: nft_mint,
+            origin_chain: transfer.origin_chain,
+            origin_contract_address: transfer.origin_contract_address,
+            nft_id: transfer.nft_id,
+            bridge_txid: transfer.bridge_txid,
+        });
+
+        Ok(())
+    }
+
+    /// Delivers an inbound transfer whatever the NFT's origin, like `pull-from-bridge` of the
+    /// Stacks bridge. The path follows on-chain state rather than the accounts the relayer
+    /// passes: an NFT held in custody is unlocked, otherwise it must belong to a wrapped
+    /// collection and is minted again, or created on its first entry.
+    pub fn bridge_in(
+        ctx: Context<BridgeIn>,
+        src_chain: ChainId,
+        transfer: InboundTransfer,
+        attestation: Attestation,
+    ) -> Result<()> {
+        require!(
+            ctx.accounts.collection_mapping.challenge_period == 0,
+            BridgeError::ChallengeWindowRequired
+        );
+
+        // Once known, an NFT always comes back as the same mint
+        let nft_mint = ctx.accounts.nft_mint.key();
+        let known_mint = ctx.accounts.nft_info_account.mint_address;
+        if known_mint != Pubkey::default() {
+            require_keys_eq!(nft_mint, known_mint, ErrorCode::ConstraintAddress);
+        }
+
+        // The attestation is what authorises the transfer, not the relayer
+        verify_inbound_attestation(
+            &attestation,
+            &ctx.accounts.bridge_config,
+            ctx.accounts.guardian_set.as_ref(),
+            ctx.accounts.instructions.as_ref(),
+            src_chain,
+            &transfer,
+            &nft_mint,
+        )?;
+
+        let minted = deliver_inbound(
+            &ctx.accounts.custody_accounts(),
+            || ctx.accounts.creator_accounts(),
+            &ctx.accounts.collection_mapping.collection,
+            &ctx.accounts.collection_info_account,
+            known_mint,
+            ctx.bumps.bridge_pda,
+            src_chain,
+            &transfer,
+        )?;
+
+        let nft_info_account = &mut ctx.accounts.nft_info_account;
+        nft_info_account.mint_address = nft_mint;
+        nft_info_account.origin_chain = transfer.origin_chain;
+        nft_info_account.nonce = transfer.nonce + 1;
+
+        let processed_transfer = &mut ctx.accounts.processed_transfer;
+        processed_transfer.processed = true;
+        processed_transfer.mint = nft_mint;
+        processed_transfer.receiver = transfer.receiver;
+        processed_transfer.processed_at = Clock::get()?.unix_timestamp;
+        processed_transfer.bump = ctx.bumps.processed_transfer;
+
+        emit!(NftBridgedIn {
+            src_chain,
+            receiver: transfer.receiver,
+            mint: nft_mint,
+            origin_chain: transfer.origin_chain,
+            origin_contract_address: transfer.origin_contract_address,
+            nft_id: transfer.nft_id,
+            bridge_txid: transfer.bridge_txid,
+            nonce: transfer.nonce,
+            minted,
+        });
+
+        Ok(())
+    }
+
+    pub fn store_nft_info_in_bridge(
+        ctx: Context<StoreNftInfoInBridge>,
+        origin_chain: ChainId,            // origin_chain passed from client
+        _origin_contract_address: String, // only part of the nft_info seeds
+        mint_address: Pubkey,
+        _nft_id: [u8; 32],
+    ) -> Result<()> {
+        let nft_info_account = &mut ctx.accounts.nft_info_account;
+
+        nft_info_account.mint_address = mint_address;
+        nft_info_account.origin_chain = origin_chain;
+
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct InitializeBridge<'info> {
     // Derived PDAs
     #[account(
-        mut,
+        init_if_needed,
+        payer = signer,
         seeds=[BRIDGE.as_bytes()],
-        bump
+        bump,
+        space = 8 + 8
     )]
     bridge_pda: AccountInfo<'info>,
 
     #[account(
-        mut,
-        seeds = [&nft_id.to_le_bytes(), origin_chain.as_bytes(), origin_contract_address.as_bytes()],
+        init,
+        payer = signer,
+        seeds=[BRIDGE_CONFIG.as_bytes()],
         bump,
-        token::mint=mint_of_token_being_sent,
-        token::authority=bridge_pda,
+        space = 8 + BridgeConfig::INIT_SPACE
     )]
-    nft_token_account: Account<'info, TokenAccount>,
+    pub bridge_config: Account<'info, BridgeConfig>,
 
-    mint_of_token_being_sent: Account<'info, Mint>,
+    #[account(
+        init,
+        payer = signer,
+        seeds=[TREASURY.as_bytes()],
+        bump,
+        space = 8 + Treasury::INIT_SPACE
+    )]
+    pub treasury_pda: Account<'info, Treasury>,
 
-    #[account(mut)]
-    pub receiver: AccountInfo<'info>,
+    // Only the upgrade authority of this program may create the config
+    #[account(constraint = program.programdata_address()? == Some(program_data.key()))]
+    pub program: Program<'info, crate::program::AnchorNftCollection>,
 
-    #[account(init_if_needed , payer = signer ,
-    associated_token::mint = mint_of_token_being_sent,
-    associated_token::authority = receiver)]
-    receiver_token_account: Account<'info, TokenAccount>,
+    #[account(constraint = program_data.upgrade_authority_address == Some(signer.key()) @ BridgeError::Unauthorized)]
+    pub program_data: Account<'info, ProgramData>,
 
-    //Change it to the address you want
-    #[account(mut,address=pubkey!("7QHySLfCkeSBUGxtqM3WdeKv9X4YUXMeTn3gr5fRPCGU"))]
+    #[account(mut)]
     signer: Signer<'info>,
     system_program: Program<'info, System>,
     token_program: Program<'info, Token>,
-    associated_token_program: Program<'info, AssociatedToken>,
     rent: Sysvar<'info, Rent>,
 }
 
 #[derive(Accounts)]
-#[instruction(origin_chain: String , origin_contract_address:String ,mint_address: Pubkey, nft_id: u64)]
-pub struct StoreNftInfoInBridge<'info> {
+pub struct UpdateBridgeConfig<'info> {
+    #[account(
+        mut,
+        seeds=[BRIDGE_CONFIG.as_bytes()],
+        bump = bridge_config.bump,
+        has_one = admin @ BridgeError::Unauthorized
+    )]
+    pub bridge_config: Account<'info, BridgeConfig>,
+
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ManageBridgeConfig<'info> {
+    #[account(
+        mut,
+        seeds=[BRIDGE_CONFIG.as_bytes()],
+        bump = bridge_config.bump
+    )]
+    pub bridge_config: Account<'info, BridgeConfig>,
+
+    #[account(
+        seeds=[ROLE.as_bytes(), &[Role::Manager as u8], signer.key().as_ref()],
+        bump = signer_role.bump
+    )]
+    pub signer_role: Account<'info, RoleAssignment>,
+
+    pub signer: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(chain: ChainId)]
+pub struct RegisterChain<'info> {
     #[account(
         init_if_needed,
-        // seeds = [NFT_INFO.as_bytes(),nft_id.as_bytes(), origin_chain.as_bytes(), origin_contract_address.as_bytes()],
-        seeds = [NFT_INFO.as_bytes(),&nft_id.to_le_bytes(), origin_chain.as_bytes(), origin_contract_address.as_bytes()],
+        payer = signer,
+        seeds=[CHAIN_CONFIG.as_bytes(), &[chain as u8]],
         bump,
+        space = 8 + ChainConfig::INIT_SPACE
+    )]
+    pub chain_config: Account<'info, ChainConfig>,
+
+    #[account(
+        seeds=[ROLE.as_bytes(), &[Role::Manager as u8], signer.key().as_ref()],
+        bump = signer_role.bump
+    )]
+    pub signer_role: Account<'info, RoleAssignment>,
+
+    #[account(mut)]
+    pub signer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(chain: ChainId)]
+pub struct UpdateChainConfig<'info> {
+    #[account(
+        mut,
+        seeds=[CHAIN_CONFIG.as_bytes(), &[chain as u8]],
+        bump = chain_config.bump
+    )]
+    pub chain_config: Account<'info, ChainConfig>,
+
+    #[account(
+        seeds=[ROLE.as_bytes(), &[Role::Manager as u8], signer.key().as_ref()],
+        bump = signer_role.bump
+    )]
+    pub signer_role: Account<'info, RoleAssignment>,
+
+    pub signer: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(collection: Pubkey, dst_chain: ChainId)]
+pub struct AddCollection<'info> {
+    #[account(
+        init_if_needed,
         payer = signer,
-        space = 8 +std::mem::size_of::<NftInfoInBridge>(),
+        seeds=[COLLECTION_MAPPING.as_bytes(), collection.as_ref(), &[dst_chain as u8]],
+        bump,
+        space = 8 + CollectionMapping::INIT_SPACE
     )]
-    pub nft_info_account: Box<Account<'info, NftInfoInBridge>>,
+    pub collection_mapping: Account<'info, CollectionMapping>,
+
+    #[account(
+        seeds=[ROLE.as_bytes(), &[Role::Manager as u8], signer.key().as_ref()],
+        bump = signer_role.bump
+    )]
+    pub signer_role: Account<'info, RoleAssignment>,
 
-    #[account(mut,address=pubkey!("7QHySLfCkeSBUGxtqM3WdeKv9X4YUXMeTn3gr5fRPCGU"))]
+    #[account(mut)]
     pub signer: Signer<'info>,
     pub system_program: Program<'info, System>,
 }
 
-#[account]
-pub struct NftInfoInBridge {
-    pub mint_address: Pubkey,
+#[derive(Accounts)]
+#[instruction(collection: Pubkey, dst_chain: ChainId)]
+pub struct UpdateCollectionMapping<'info> {
+    #[account(
+        mut,
+        seeds=[COLLECTION_MAPPING.as_bytes(), collection.as_ref(), &[dst_chain as u8]],
+        bump = collection_mapping.bump
+    )]
+    pub collection_mapping: Account<'info, CollectionMapping>,
+
+    #[account(
+        seeds=[ROLE.as_bytes(), &[Role::Manager as u8], signer.key().as_ref()],
+        bump = signer_role.bump
+    )]
+    pub signer_role: Account<'info, RoleAssignment>,
+
+    pub signer: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(collection: Pubkey, dst_chain: ChainId)]
+pub struct RemoveCollectionMapping<'info> {
+    #[account(
+        mut,
+        close = signer,
+        seeds=[COLLECTION_MAPPING.as_bytes(), collection.as_ref(), &[dst_chain as u8]],
+        bump = collection_mapping.bump
+    )]
+    pub collection_mapping: Account<'info, CollectionMapping>,
+
+    #[account(
+        seeds=[ROLE.as_bytes(), &[Role::Manager as u8], signer.key().as_ref()],
+        bump = signer_role.bump
+    )]
+    pub signer_role: Account<'info, RoleAssignment>,
+
+    #[account(mut)]
+    pub signer: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct PauseBridge<'info> {
+    #[account(
+        mut,
+        seeds=[BRIDGE_CONFIG.as_bytes()],
+        bump = bridge_config.bump
+    )]
+    pub bridge_config: Account<'info, BridgeConfig>,
+
+    #[account(
+        seeds=[ROLE.as_bytes(), &[Role::Pauser as u8], signer.key().as_ref()],
+        bump = signer_role.bump
+    )]
+    pub signer_role: Account<'info, RoleAssignment>,
+
+    pub signer: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(chain: ChainId)]
+pub struct PauseChain<'info> {
+    #[account(
+        mut,
+        seeds=[CHAIN_CONFIG.as_bytes(), &[chain as u8]],
+        bump = chain_config.bump
+    )]
+    pub chain_config: Account<'info, ChainConfig>,
+
+    #[account(
+        seeds=[ROLE.as_bytes(), &[Role::Pauser as u8], signer.key().as_ref()],
+        bump = signer_role.bump
+    )]
+    pub signer_role: Account<'info, RoleAssignment>,
+
+    pub signer: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(collection: Pubkey, dst_chain: ChainId)]
+pub struct PauseCollection<'info> {
+    #[account(
+        mut,
+        seeds=[COLLECTION_MAPPING.as_bytes(), collection.as_ref(), &[dst_chain as u8]],
+        bump = collection_mapping.bump
+    )]
+    pub collection_mapping: Account<'info, CollectionMapping>,
+
+    #[account(
+        seeds=[ROLE.as_bytes(), &[Role::Pauser as u8], signer.key().as_ref()],
+        bump = signer_role.bump
+    )]
+    pub signer_role: Account<'info, RoleAssignment>,
+
+    pub signer: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(dst_chain: ChainId)]
+pub struct QuoteFee<'info> {
+    #[account(seeds=[BRIDGE_CONFIG.as_bytes()], bump = bridge_config.bump)]
+    pub bridge_config: Account<'info, BridgeConfig>,
+
+    #[account(seeds=[CHAIN_CONFIG.as_bytes(), &[dst_chain as u8]], bump = chain_config.bump)]
+    pub chain_config: Account<'info, ChainConfig>,
+}
+
+#[derive(Accounts)]
+pub struct WithdrawFees<'info> {
+    #[account(
+        seeds=[BRIDGE_CONFIG.as_bytes()],
+        bump = bridge_config.bump,
+        has_one = admin @ BridgeError::Unauthorized,
+        has_one = treasury
+    )]
+    pub bridge_config: Account<'info, BridgeConfig>,
+
+    #[account(
+        mut,
+        seeds=[TREASURY.as_bytes()],
+        bump = treasury_pda.bump
+    )]
+    pub treasury_pda: Account<'info, Treasury>,
+
+    /// CHECK: fee recipient stored in the bridge config
+    #[account(mut)]
+    pub treasury: UncheckedAccount<'info>,
+
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeGuardianSet<'info> {
+    #[account(
+        mut,
+        seeds=[BRIDGE_CONFIG.as_bytes()],
+        bump = bridge_config.bump,
+        has_one = admin @ BridgeError::Unauthorized
+    )]
+    pub bridge_config: Account<'info, BridgeConfig>,
+
+    #[account(
+        init,
+        payer = admin,
+        seeds=[GUARDIAN_SET.as_bytes(), &0u32.to_le_bytes()],
+        bump,
+        space = 8 + GuardianSet::INIT_SPACE
+    )]
+    pub guardian_set: Account<'info, GuardianSet>,
+
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ProposeGuardianSet<'info> {
+    #[account(
+        seeds=[BRIDGE_CONFIG.as_bytes()],
+        bump = bridge_config.bump,
+        has_one = admin @ BridgeError::Unauthorized
+    )]
+    pub bridge_config: Account<'info, BridgeConfig>,
+
+    // A pending proposal may be replaced until it is activated
+    #[account(
+        init_if_needed,
+        payer = admin,
+        seeds=[GUARDIAN_SET.as_bytes(), &(bridge_config.guardian_set_index + 1).to_le_bytes()],
+        bump,
+        space = 8 + GuardianSet::INIT_SPACE,
+        constraint = guardian_set.activated_at == 0 @ BridgeError::InvalidGuardianSet
+    )]
+    pub guardian_set: Account<'info, GuardianSet>,
+
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ActivateGuardianSet<'info> {
+    #[account(
+        mut,
+        seeds=[BRIDGE_CONFIG.as_bytes()],
+        bump = bridge_config.bump,
+        has_one = admin @ BridgeError::Unauthorized
+    )]
+    pub bridge_config: Account<'info, BridgeConfig>,
+
+    #[account(
+        mut,
+        seeds=[GUARDIAN_SET.as_bytes(), &bridge_config.guardian_set_index.wrapping_sub(1).to_le_bytes()],
+        bump = previous_guardian_set.bump
+    )]
+    pub previous_guardian_set: Option<Account<'info, GuardianSet>>,
+
+    #[account(
+        mut,
+        seeds=[GUARDIAN_SET.as_bytes(), &bridge_config.guardian_set_index.to_le_bytes()],
+        bump = current_guardian_set.bump
+    )]
+    pub current_guardian_set: Account<'info, GuardianSet>,
+
+    #[account(
+        mut,
+        seeds=[GUARDIAN_SET.as_bytes(), &(bridge_config.guardian_set_index + 1).to_le_bytes()],
+        bump = new_guardian_set.bump,
+        constraint = new_guardian_set.activated_at == 0 @ BridgeError::InvalidGuardianSet
+    )]
+    pub new_guardian_set: Account<'info, GuardianSet>,
+
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(role: Role, member: Pubkey)]
+pub struct GrantRole<'info> {
+    #[account(
+        seeds=[BRIDGE_CONFIG.as_bytes()],
+        bump = bridge_config.bump,
+        has_one = admin @ BridgeError::Unauthorized
+    )]
+    pub bridge_config: Account<'info, BridgeConfig>,
+
+    #[account(
+        init,
+        payer = admin,
+        seeds=[ROLE.as_bytes(), &[role as u8], member.as_ref()],
+        bump,
+        space = 8 + RoleAssignment::INIT_SPACE
+    )]
+    pub role_assignment: Account<'info, RoleAssignment>,
+
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(role: Role, member: Pubkey)]
+pub struct RevokeRole<'info> {
+    #[account(
+        seeds=[BRIDGE_CONFIG.as_bytes()],
+        bump = bridge_config.bump,
+        has_one = admin @ BridgeError::Unauthorized
+    )]
+    pub bridge_config: Account<'info, BridgeConfig>,
+
+    #[account(
+        mut,
+        close = admin,
+        seeds=[ROLE.as_bytes(), &[role as u8], member.as_ref()],
+        bump = role_assignment.bump
+    )]
+    pub role_assignment: Account<'info, RoleAssignment>,
+
+    #[account(mut)]
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(
+        origin_chain: ChainId,
+        origin_contract_address: String,
+        nft_id: [u8; 32],
+        amount: u64,
+        coll_id: String,
+        src_address: String,
+        dst_chain: ChainId,
+        dst_address: String,)]
+pub struct InitializeAndLockNftFee<'info> {
+    // Derived PDAs and accounts for initialization
+    #[account(
+        mut,
+        seeds=[BRIDGE.as_bytes()],
+        bump
+    )]
+    pub bridge_pda: AccountInfo<'info>, // `mut` is needed because the PDA will be initialized
+
+    // Writable so wrapped NFTs can be burned
+    #[account(mut)]
+    pub mint_of_token_being_sent: Account<'info, Mint>,
+
+    /// CHECK: metaplex metadata of the NFT, deserialized in the instruction
+    #[account(address = MetadataAccount::find_pda(&mint_of_token_being_sent.key()).0)]
+    pub metadata_account: UncheckedAccount<'info>,
+
+    #[account(
+        seeds=[COLLECTION_MAPPING.as_bytes(), collection_mapping.collection.as_ref(), &[dst_chain as u8]],
+        bump = collection_mapping.bump,
+        constraint = !collection_mapping.paused @ BridgeError::BridgePaused
+    )]
+    pub collection_mapping: Account<'info, CollectionMapping>,
+
+    // Custody of a Solana NFT, left out for a wrapped NFT since it is burned
+    #[account(
+        init_if_needed,
+        payer = signer,
+        seeds = [&origin_nft_seed(origin_chain, &origin_contract_address, &nft_id)],
+        token::mint = mint_of_token_being_sent,
+        token::authority = bridge_pda,
+        bump
+    )]
+    pub nft_token_account: Option<Account<'info, TokenAccount>>, // `mut` is needed because the account will be initialized
+
+    #[account(
+        init_if_needed,
+        seeds = [NFT_INFO.as_bytes(), &origin_nft_seed(origin_chain, &origin_contract_address, &nft_id)],
+        bump,
+        payer = signer,
+        space = 8 + std::mem::size_of::<NftInfoInBridge>(),
+    )]
+    pub nft_info_account: Box<Account<'info, NftInfoInBridge>>,
+
+    /// CHECK: collection_creator `CollectionInfo`, only needed for wrapped collections
+    pub collection_info_account: Option<UncheckedAccount<'info>>,
+
+    #[account(
+        mut,
+        constraint = sender_token_account.owner == signer.key()
+            && sender_token_account.mint == mint_of_token_being_sent.key()
+            && sender_token_account.amount == 1 @ BridgeError::InvalidNftOwner
+    )]
+    pub sender_token_account: Account<'info, TokenAccount>, // `mut` is needed because the account will be used for transfer
+
+    #[account(
+        seeds=[BRIDGE_CONFIG.as_bytes()],
+        bump = bridge_config.bump,
+        constraint = !bridge_config.paused @ BridgeError::BridgePaused
+    )]
+    pub bridge_config: Account<'info, BridgeConfig>,
+
+    #[account(
+        seeds=[CHAIN_CONFIG.as_bytes(), &[dst_chain as u8]],
+        bump = chain_config.bump,
+        constraint = chain_config.enabled @ BridgeError::ChainNotAllowed,
+        constraint = !chain_config.paused @ BridgeError::BridgePaused
+    )]
+    pub chain_config: Account<'info, ChainConfig>,
+
+    #[account(
+        mut,
+        seeds=[TREASURY.as_bytes()],
+        bump = treasury_pda.bump
+    )]
+    pub treasury_pda: Account<'info, Treasury>,
+
+    #[account(mut)]
+    pub signer: Signer<'info>, // `mut` is needed because the signer is part of the transaction
+
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+#[derive(Accounts)]
+#[instruction(origin_chain: ChainId,
+        origin_contract_address: String,
+        nft_ids: Vec<[u8; 32]>,
+        amount: u64,
+        coll_id: String,
+        src_address: String,
+        dst_chain: ChainId,
+        dst_address: String,)]
+pub struct LockNftsBatch<'info> {
+    #[account(
+        seeds=[BRIDGE.as_bytes()],
+        bump
+    )]
+    pub bridge_pda: AccountInfo<'info>,
+
+    #[account(
+        seeds=[COLLECTION_MAPPING.as_bytes(), collection_mapping.collection.as_ref(), &[dst_chain as u8]],
+        bump = collection_mapping.bump,
+        constraint = !collection_mapping.paused @ BridgeError::BridgePaused
+    )]
+    pub collection_mapping: Account<'info, CollectionMapping>,
+
+    /// CHECK: collection_creator `CollectionInfo`, only needed for wrapped collections
+    pub collection_info_account: Option<UncheckedAccount<'info>>,
+
+    #[account(
+        seeds=[BRIDGE_CONFIG.as_bytes()],
+        bump = bridge_config.bump,
+        constraint = !bridge_config.paused @ BridgeError::BridgePaused
+    )]
+    pub bridge_config: Account<'info, BridgeConfig>,
+
+    #[account(
+        seeds=[CHAIN_CONFIG.as_bytes(), &[dst_chain as u8]],
+        bump = chain_config.bump,
+        constraint = chain_config.enabled @ BridgeError::ChainNotAllowed,
+        constraint = !chain_config.paused @ BridgeError::BridgePaused
+    )]
+    pub chain_config: Account<'info, ChainConfig>,
+
+    #[account(
+        mut,
+        seeds=[TREASURY.as_bytes()],
+        bump = treasury_pda.bump
+    )]
+    pub treasury_pda: Account<'info, Treasury>,
+
+    #[account(mut)]
+    pub signer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+#[instruction(origin_chain: ChainId,
+        origin_contract_address: String,
+        nft_id: [u8; 32],
+        coll_id: String,
+        src_chain: ChainId,
+        src_address: String,
+        dst_address: String,
+        bridge_txid: String,
+        nonce: u64,
+        attestation: Attestation,)]
+pub struct UnlockNft<'info> {
+    // Derived PDAs
+    #[account(
+        mut,
+        seeds=[BRIDGE.as_bytes()],
+        bump
+    )]
+    bridge_pda: AccountInfo<'info>,
+
+    /// CHECK: custody token account, empty or missing for a wrapped NFT that was burned
+    #[account(
+        mut,
+        seeds = [&origin_nft_seed(origin_chain, &origin_contract_address, &nft_id)],
+        bump
+    )]
+    nft_token_account: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [NFT_INFO.as_bytes(), &origin_nft_seed(origin_chain, &origin_contract_address, &nft_id)],
+        bump,
+        constraint = nft_info_account.nonce == nonce @ BridgeError::InvalidNonce
+    )]
+    pub nft_info_account: Box<Account<'info, NftInfoInBridge>>,
+
+    // Writable so a burned wrapped NFT can be minted again
+    #[account(mut)]
+    mint_of_token_being_sent: Account<'info, Mint>,
+
+    /// CHECK: metaplex metadata of the NFT, deserialized in the instruction
+    #[account(address = MetadataAccount::find_pda(&mint_of_token_being_sent.key()).0)]
+    pub metadata_account: UncheckedAccount<'info>,
+
+    #[account(
+        seeds=[COLLECTION_MAPPING.as_bytes(), collection_mapping.collection.as_ref(), &[src_chain as u8]],
+        bump = collection_mapping.bump,
+        constraint = !collection_mapping.paused @ BridgeError::BridgePaused
+    )]
+    pub collection_mapping: Account<'info, CollectionMapping>,
+
+    #[account(mut)]
+    pub receiver: AccountInfo<'info>,
+
+    #[account(init_if_needed , payer = signer ,
+    associated_token::mint = mint_of_token_being_sent,
+    associated_token::authority = receiver)]
+    receiver_token_account: Account<'info, TokenAccount>,
+
+    #[account(
+        seeds=[BRIDGE_CONFIG.as_bytes()],
+        bump = bridge_config.bump,
+        constraint = !bridge_config.paused @ BridgeError::BridgePaused
+    )]
+    pub bridge_config: Account<'info, BridgeConfig>,
+
+    #[account(
+        seeds=[CHAIN_CONFIG.as_bytes(), &[src_chain as u8]],
+        bump = src_chain_config.bump,
+        constraint = src_chain_config.enabled @ BridgeError::ChainNotAllowed,
+        constraint = !src_chain_config.paused @ BridgeError::BridgePaused
+    )]
+    pub src_chain_config: Account<'info, ChainConfig>,
+
+    // One per inbound transfer, a second unlock with the same id is rejected
+    #[account(
+        init_if_needed,
+        payer = signer,
+        seeds=[PROCESSED_TRANSFER.as_bytes(), &[src_chain as u8], &hash(bridge_txid.as_bytes()).to_bytes()],
+        bump,
+        space = 8 + ProcessedTransfer::INIT_SPACE,
+        constraint = !processed_transfer.processed @ BridgeError::TransferAlreadyProcessed
+    )]
+    pub processed_transfer: Account<'info, ProcessedTransfer>,
+
+    // Only needed for guardian attestations
+    #[account(
+        seeds=[GUARDIAN_SET.as_bytes(), &guardian_set.index.to_le_bytes()],
+        bump = guardian_set.bump
+    )]
+    pub guardian_set: Option<Account<'info, GuardianSet>>,
+
+    /// CHECK: instructions sysvar, read for the guardian Ed25519 instruction
+    #[account(address = sysvar::instructions::ID)]
+    pub instructions: Option<UncheckedAccount<'info>>,
+
+    // Wrapped collections only, the accounts are checked by collection_creator
+    /// CHECK: collection_creator `CollectionInfo`
+    pub collection_info_account: Option<UncheckedAccount<'info>>,
+    /// CHECK: collection_creator program
+    #[account(address = COLLECTION_CREATOR_PROGRAM_ID)]
+    pub creator_program: Option<UncheckedAccount<'info>>,
+    /// CHECK:
+    pub creator_config: Option<UncheckedAccount<'info>>,
+    /// CHECK:
+    pub collection_mint: Option<UncheckedAccount<'info>>,
+    /// CHECK:
+    #[account(mut)]
+    pub master_edition: Option<UncheckedAccount<'info>>,
+    pub token_metadata_program: Option<Program<'info, Metadata>>,
+
+    // Any relayer may submit, the unlock is authorised by the attestation
+    #[account(mut)]
+    signer: Signer<'info>,
+    system_program: Program<'info, System>,
+    token_program: Program<'info, Token>,
+    associated_token_program: Program<'info, AssociatedToken>,
+    rent: Sysvar<'info, Rent>,
+}
+
+#[derive(Accounts)]
+#[instruction(origin_chain: ChainId,
+        origin_contract_address: String,
+        nft_ids: Vec<[u8; 32]>,
+        nonces: Vec<u64>,
+        coll_id: String,
+        src_chain: ChainId,
+        src_address: String,
+        dst_address: String,
+        bridge_txid: String,
+        attestation: Attestation,)]
+pub struct UnlockNftsBatch<'info> {
+    #[account(
+        seeds=[BRIDGE.as_bytes()],
+        bump
+    )]
+    bridge_pda: AccountInfo<'info>,
+
+    #[account(
+        seeds=[COLLECTION_MAPPING.as_bytes(), collection_mapping.collection.as_ref(), &[src_chain as u8]],
+        bump = collection_mapping.bump,
+        constraint = !collection_mapping.paused @ BridgeError::BridgePaused
+    )]
+    pub collection_mapping: Account<'info, CollectionMapping>,
+
+    pub receiver: AccountInfo<'info>,
+
+    #[account(
+        seeds=[BRIDGE_CONFIG.as_bytes()],
+        bump = bridge_config.bump,
+        constraint = !bridge_config.paused @ BridgeError::BridgePaused
+    )]
+    pub bridge_config: Account<'info, BridgeConfig>,
+
+    #[account(
+        seeds=[CHAIN_CONFIG.as_bytes(), &[src_chain as u8]],
+        bump = src_chain_config.bump,
+        constraint = src_chain_config.enabled @ BridgeError::ChainNotAllowed,
+        constraint = !src_chain_config.paused @ BridgeError::BridgePaused
+    )]
+    pub src_chain_config: Account<'info, ChainConfig>,
+
+    // Shared with single unlocks, the batch consumes its bridge txid once
+    #[account(
+        init_if_needed,
+        payer = signer,
+        seeds=[PROCESSED_TRANSFER.as_bytes(), &[src_chain as u8], &hash(bridge_txid.as_bytes()).to_bytes()],
+        bump,
+        space = 8 + ProcessedTransfer::INIT_SPACE + 32 * nft_ids.len(),
+        constraint = !processed_transfer.processed @ BridgeError::TransferAlreadyProcessed
+    )]
+    pub processed_transfer: Account<'info, ProcessedTransfer>,
+
+    // Only needed for guardian attestations
+    #[account(
+        seeds=[GUARDIAN_SET.as_bytes(), &guardian_set.index.to_le_bytes()],
+        bump = guardian_set.bump
+    )]
+    pub guardian_set: Option<Account<'info, GuardianSet>>,
+
+    /// CHECK: instructions sysvar, read for the guardian Ed25519 instruction
+    #[account(address = sysvar::instructions::ID)]
+    pub instructions: Option<UncheckedAccount<'info>>,
+
+    // Wrapped collections only, the accounts are checked by collection_creator
+    /// CHECK: collection_creator `CollectionInfo`
+    pub collection_info_account: Option<UncheckedAccount<'info>>,
+    /// CHECK: collection_creator program
+    #[account(address = COLLECTION_CREATOR_PROGRAM_ID)]
+    pub creator_program: Option<UncheckedAccount<'info>>,
+    /// CHECK:
+    pub creator_config: Option<UncheckedAccount<'info>>,
+    /// CHECK:
+    pub collection_mint: Option<UncheckedAccount<'info>>,
+    pub token_metadata_program: Option<Program<'info, Metadata>>,
+
+    // Any relayer may submit, the unlock is authorised by the attestation
+    #[account(mut)]
+    signer: Signer<'info>,
+    system_program: Program<'info, System>,
+    token_program: Program<'info, Token>,
+    associated_token_program: Program<'info, AssociatedToken>,
+}
+
+#[derive(Accounts)]
+#[instruction(origin_chain: ChainId,
+        origin_contract_address: String,
+        nft_id: [u8; 32],
+        coll_id: String,
+        src_chain: ChainId,
+        src_address: String,
+        dst_address: String,
+        bridge_txid: String,
+        nonce: u64,
+        attestation: Attestation,)]
+pub struct ProposeUnlock<'info> {
+    #[account(
+        seeds = [NFT_INFO.as_bytes(), &origin_nft_seed(origin_chain, &origin_contract_address, &nft_id)],
+        bump,
+        constraint = nft_info_account.nonce == nonce @ BridgeError::InvalidNonce
+    )]
+    pub nft_info_account: Box<Account<'info, NftInfoInBridge>>,
+
+    pub mint_of_token_being_sent: Account<'info, Mint>,
+
+    /// CHECK: metaplex metadata of the NFT, deserialized in the instruction
+    #[account(address = MetadataAccount::find_pda(&mint_of_token_being_sent.key()).0)]
+    pub metadata_account: UncheckedAccount<'info>,
+
+    #[account(
+        seeds=[COLLECTION_MAPPING.as_bytes(), collection_mapping.collection.as_ref(), &[src_chain as u8]],
+        bump = collection_mapping.bump,
+        constraint = !collection_mapping.paused @ BridgeError::BridgePaused
+    )]
+    pub collection_mapping: Account<'info, CollectionMapping>,
+
+    /// CHECK: recipient of the NFT once the unlock is executed
+    pub receiver: UncheckedAccount<'info>,
+
+    #[account(
+        seeds=[BRIDGE_CONFIG.as_bytes()],
+        bump = bridge_config.bump,
+        constraint = !bridge_config.paused @ BridgeError::BridgePaused
+    )]
+    pub bridge_config: Account<'info, BridgeConfig>,
+
+    #[account(
+        seeds=[CHAIN_CONFIG.as_bytes(), &[src_chain as u8]],
+        bump = src_chain_config.bump,
+        constraint = src_chain_config.enabled @ BridgeError::ChainNotAllowed,
+        constraint = !src_chain_config.paused @ BridgeError::BridgePaused
+    )]
+    pub src_chain_config: Account<'info, ChainConfig>,
+
+    #[account(
+        init_if_needed,
+        payer = signer,
+        seeds=[PROCESSED_TRANSFER.as_bytes(), &[src_chain as u8], &hash(bridge_txid.as_bytes()).to_bytes()],
+        bump,
+        space = 8 + ProcessedTransfer::INIT_SPACE,
+        constraint = !processed_transfer.vetoed @ BridgeError::TransferVetoed,
+        constraint = !processed_transfer.processed @ BridgeError::TransferAlreadyProcessed
+    )]
+    pub processed_transfer: Account<'info, ProcessedTransfer>,
+
+    #[account(
+        init,
+        payer = signer,
+        seeds=[PENDING_UNLOCK.as_bytes(), &[src_chain as u8], &hash(bridge_txid.as_bytes()).to_bytes()],
+        bump,
+        space = 8 + PendingUnlock::INIT_SPACE
+    )]
+    pub pending_unlock: Box<Account<'info, PendingUnlock>>,
+
+    // Only needed for guardian attestations
+    #[account(
+        seeds=[GUARDIAN_SET.as_bytes(), &guardian_set.index.to_le_bytes()],
+        bump = guardian_set.bump
+    )]
+    pub guardian_set: Option<Account<'info, GuardianSet>>,
+
+    /// CHECK: instructions sysvar, read for the guardian Ed25519 instruction
+    #[account(address = sysvar::instructions::ID)]
+    pub instructions: Option<UncheckedAccount<'info>>,
+
+    #[account(mut)]
+    pub signer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ExecuteUnlock<'info> {
+    #[account(
+        mut,
+        close = proposer,
+        seeds=[PENDING_UNLOCK.as_bytes(), &[pending_unlock.src_chain as u8], &hash(pending_unlock.bridge_txid.as_bytes()).to_bytes()],
+        bump = pending_unlock.bump
+    )]
+    pub pending_unlock: Box<Account<'info, PendingUnlock>>,
+
+    /// CHECK: gets the pending unlock rent back
+    #[account(mut, address = pending_unlock.proposer)]
+    pub proposer: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        seeds=[BRIDGE.as_bytes()],
+        bump
+    )]
+    bridge_pda: AccountInfo<'info>,
+
+    /// CHECK: custody token account, empty or missing for a wrapped NFT that was burned
+    #[account(
+        mut,
+        seeds = [&origin_nft_seed(pending_unlock.origin_chain, &pending_unlock.origin_contract_address, &pending_unlock.nft_id)],
+        bump
+    )]
+    nft_token_account: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [NFT_INFO.as_bytes(), &origin_nft_seed(pending_unlock.origin_chain, &pending_unlock.origin_contract_address, &pending_unlock.nft_id)],
+        bump,
+        constraint = nft_info_account.nonce == pending_unlock.nonce @ BridgeError::InvalidNonce
+    )]
+    pub nft_info_account: Box<Account<'info, NftInfoInBridge>>,
+
+    // Writable so a burned wrapped NFT can be minted again
+    #[account(mut, address = pending_unlock.mint)]
+    mint_of_token_being_sent: Account<'info, Mint>,
+
+    /// CHECK: metaplex metadata of the NFT
+    #[account(address = MetadataAccount::find_pda(&mint_of_token_being_sent.key()).0)]
+    pub metadata_account: UncheckedAccount<'info>,
+
+    /// CHECK: recipient recorded by propose_unlock
+    #[account(address = pending_unlock.receiver)]
+    pub receiver: UncheckedAccount<'info>,
+
+    #[account(init_if_needed , payer = signer ,
+    associated_token::mint = mint_of_token_being_sent,
+    associated_token::authority = receiver)]
+    receiver_token_account: Account<'info, TokenAccount>,
+
+    #[account(
+        seeds=[BRIDGE_CONFIG.as_bytes()],
+        bump = bridge_config.bump,
+        constraint = !bridge_config.paused @ BridgeError::BridgePaused
+    )]
+    pub bridge_config: Account<'info, BridgeConfig>,
+
+    #[account(
+        seeds=[CHAIN_CONFIG.as_bytes(), &[pending_unlock.src_chain as u8]],
+        bump = src_chain_config.bump,
+        constraint = src_chain_config.enabled @ BridgeError::ChainNotAllowed,
+        constraint = !src_chain_config.paused @ BridgeError::BridgePaused
+    )]
+    pub src_chain_config: Account<'info, ChainConfig>,
+
+    #[account(
+        seeds=[COLLECTION_MAPPING.as_bytes(), pending_unlock.collection.as_ref(), &[pending_unlock.src_chain as u8]],
+        bump = collection_mapping.bump,
+        constraint = !collection_mapping.paused @ BridgeError::BridgePaused
+    )]
+    pub collection_mapping: Account<'info, CollectionMapping>,
+
+    // Wrapped collections only, the accounts are checked by collection_creator
+    /// CHECK: collection_creator `CollectionInfo`
+    pub collection_info_account: Option<UncheckedAccount<'info>>,
+    /// CHECK: collection_creator program
+    #[account(address = COLLECTION_CREATOR_PROGRAM_ID)]
+    pub creator_program: Option<UncheckedAccount<'info>>,
+    /// CHECK:
+    pub creator_config: Option<UncheckedAccount<'info>>,
+    /// CHECK:
+    pub collection_mint: Option<UncheckedAccount<'info>>,
+    /// CHECK:
+    #[account(mut)]
+    pub master_edition: Option<UncheckedAccount<'info>>,
+    pub token_metadata_program: Option<Program<'info, Metadata>>,
+    /// CHECK: instructions sysvar, read by Token Metadata
+    #[account(address = sysvar::instructions::ID)]
+    pub instructions: Option<UncheckedAccount<'info>>,
+
+    // Anyone may execute once the challenge window has passed
+    #[account(mut)]
+    signer: Signer<'info>,
+    system_program: Program<'info, System>,
+    token_program: Program<'info, Token>,
+    associated_token_program: Program<'info, AssociatedToken>,
+    rent: Sysvar<'info, Rent>,
+}
+
+#[derive(Accounts)]
+pub struct VetoUnlock<'info> {
+    #[account(
+        mut,
+        close = proposer,
+        seeds=[PENDING_UNLOCK.as_bytes(), &[pending_unlock.src_chain as u8], &hash(pending_unlock.bridge_txid.as_bytes()).to_bytes()],
+        bump = pending_unlock.bump
+    )]
+    pub pending_unlock: Box<Account<'info, PendingUnlock>>,
+
+    // Kept, so the vetoed attestation can't be proposed again
+    #[account(
+        mut,
+        seeds=[PROCESSED_TRANSFER.as_bytes(), &[pending_unlock.src_chain as u8], &hash(pending_unlock.bridge_txid.as_bytes()).to_bytes()],
+        bump = processed_transfer.bump
+    )]
+    pub processed_transfer: Account<'info, ProcessedTransfer>,
+
+    /// CHECK: gets the pending unlock rent back
+    #[account(mut, address = pending_unlock.proposer)]
+    pub proposer: UncheckedAccount<'info>,
+
+    #[account(
+        seeds=[ROLE.as_bytes(), &[Role::Watcher as u8], signer.key().as_ref()],
+        bump = signer_role.bump
+    )]
+    pub signer_role: Account<'info, RoleAssignment>,
+
+    pub signer: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(src_chain: ChainId, bridge_txid: String)]
+pub struct ClearVetoedTransfer<'info> {
+    #[account(
+        seeds=[BRIDGE_CONFIG.as_bytes()],
+        bump = bridge_config.bump,
+        has_one = admin @ BridgeError::Unauthorized
+    )]
+    pub bridge_config: Account<'info, BridgeConfig>,
+
+    #[account(
+        mut,
+        seeds=[PROCESSED_TRANSFER.as_bytes(), &[src_chain as u8], &hash(bridge_txid.as_bytes()).to_bytes()],
+        bump = processed_transfer.bump,
+        constraint = processed_transfer.vetoed @ BridgeError::TransferAlreadyProcessed
+    )]
+    pub processed_transfer: Account<'info, ProcessedTransfer>,
+
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(src_chain: ChainId, batch_id: u64)]
+pub struct PostInboundBatch<'info> {
+    #[account(
+        init,
+        payer = signer,
+        seeds=[INBOUND_BATCH.as_bytes(), &[src_chain as u8], &batch_id.to_le_bytes()],
+        bump,
+        space = 8 + InboundBatch::INIT_SPACE
+    )]
+    pub inbound_batch: Box<Account<'info, InboundBatch>>,
+
+    #[account(
+        seeds=[BRIDGE_CONFIG.as_bytes()],
+        bump = bridge_config.bump,
+        constraint = !bridge_config.paused @ BridgeError::BridgePaused
+    )]
+    pub bridge_config: Account<'info, BridgeConfig>,
+
+    #[account(
+        seeds=[CHAIN_CONFIG.as_bytes(), &[src_chain as u8]],
+        bump = src_chain_config.bump,
+        constraint = src_chain_config.enabled @ BridgeError::ChainNotAllowed,
+        constraint = !src_chain_config.paused @ BridgeError::BridgePaused
+    )]
+    pub src_chain_config: Account<'info, ChainConfig>,
+
+    // Any relayer may post, the batch is authorised by the operator signature
+    #[account(mut)]
+    pub signer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(src_chain: ChainId, batch_id: u64, index: u32, transfer: InboundTransfer)]
+pub struct ClaimWithProof<'info> {
+    #[account(
+        mut,
+        seeds=[INBOUND_BATCH.as_bytes(), &[src_chain as u8], &batch_id.to_le_bytes()],
+        bump = inbound_batch.bump
+    )]
+    pub inbound_batch: Box<Account<'info, InboundBatch>>,
+
+    #[account(
+        seeds=[BRIDGE_CONFIG.as_bytes()],
+        bump = bridge_config.bump,
+        constraint = !bridge_config.paused @ BridgeError::BridgePaused
+    )]
+    pub bridge_config: Account<'info, BridgeConfig>,
+
+    #[account(
+        seeds=[CHAIN_CONFIG.as_bytes(), &[src_chain as u8]],
+        bump = src_chain_config.bump,
+        constraint = src_chain_config.enabled @ BridgeError::ChainNotAllowed,
+        constraint = !src_chain_config.paused @ BridgeError::BridgePaused
+    )]
+    pub src_chain_config: Box<Account<'info, ChainConfig>>,
+
+    #[account(
+        seeds=[COLLECTION_MAPPING.as_bytes(), collection_mapping.collection.as_ref(), &[src_chain as u8]],
+        bump = collection_mapping.bump,
+        constraint = !collection_mapping.paused @ BridgeError::BridgePaused
+    )]
+    pub collection_mapping: Box<Account<'info, CollectionMapping>>,
+
+    // One per NFT of a bridge txid, a single source transaction may lock several token ids.
+    // The nft info nonce keeps a claimed NFT from being unlocked again on another path.
+    #[account(
+        init_if_needed,
+        payer = signer,
+        seeds=[PROCESSED_TRANSFER.as_bytes(), &[src_chain as u8], &hash(transfer.bridge_txid.as_bytes()).to_bytes(), &transfer.nft_id],
+        bump,
+        space = 8 + ProcessedTransfer::INIT_SPACE,
+        constraint = !processed_transfer.processed @ BridgeError::TransferAlreadyProcessed
+    )]
+    pub processed_transfer: Box<Account<'info, ProcessedTransfer>>,
+
+    /// CHECK: recipient committed to in the batch leaf
+    #[account(mut, address = transfer.receiver)]
+    pub receiver: UncheckedAccount<'info>,
+
+    /// CHECK: receiver's associated token account, created if needed
+    #[account(mut)]
+    pub receiver_token_account: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        seeds=[BRIDGE.as_bytes()],
+        bump
+    )]
+    bridge_pda: AccountInfo<'info>,
+
+    /// CHECK: custody token account of the NFT, which only exists once it has been locked
+    #[account(
+        mut,
+        seeds = [&origin_nft_seed(transfer.origin_chain, &transfer.origin_contract_address, &transfer.nft_id)],
+        bump
+    )]
+    pub nft_token_account: UncheckedAccount<'info>,
+
+    #[account(
+        init_if_needed,
+        payer = signer,
+        seeds = [NFT_INFO.as_bytes(), &origin_nft_seed(transfer.origin_chain, &transfer.origin_contract_address, &transfer.nft_id)],
+        bump,
+        space = 8 + std::mem::size_of::<NftInfoInBridge>(),
+        constraint = nft_info_account.nonce == transfer.nonce @ BridgeError::InvalidNonce
+    )]
+    pub nft_info_account: Box<Account<'info, NftInfoInBridge>>,
+
+    /// CHECK: mint recorded in the nft info, or the wrapped mint collection_creator derives
+    /// from the origin identifiers on the first entry of a wrapped NFT
+    #[account(mut)]
+    pub nft_mint: UncheckedAccount<'info>,
+
+    /// CHECK: metaplex metadata of the NFT
+    #[account(mut, address = MetadataAccount::find_pda(&nft_mint.key()).0)]
+    pub metadata_account: UncheckedAccount<'info>,
+
+    /// CHECK: instructions sysvar, read by Token Metadata when a wrapped NFT is minted again
+    #[account(address = sysvar::instructions::ID)]
+    pub instructions: Option<UncheckedAccount<'info>>,
+
+    // Wrapped collections only, the accounts are checked by collection_creator
+    /// CHECK: collection_creator `CollectionInfo`
+    #[account(mut)]
+    pub collection_info_account: Option<UncheckedAccount<'info>>,
+    /// CHECK: collection_creator program
+    #[account(address = COLLECTION_CREATOR_PROGRAM_ID)]
+    pub creator_program: Option<UncheckedAccount<'info>>,
+    /// CHECK:
+    pub creator_config: Option<UncheckedAccount<'info>>,
+    /// CHECK:
+    #[account(mut)]
+    pub collection_mint: Option<UncheckedAccount<'info>>,
+    /// CHECK:
+    #[account(mut)]
+    pub collection_metadata_account: Option<UncheckedAccount<'info>>,
+    /// CHECK:
+    #[account(mut)]
+    pub collection_master_edition: Option<UncheckedAccount<'info>>,
+    /// CHECK:
+    #[account(mut)]
+    pub master_edition: Option<UncheckedAccount<'info>>,
+    pub token_metadata_program: Option<Program<'info, Metadata>>,
+
+    #[account(mut)]
+    pub signer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+#[derive(Accounts)]
+#[instruction(src_chain: ChainId, transfer: InboundTransfer)]
+pub struct BridgeIn<'info> {
+    #[account(
+        mut,
+        seeds=[BRIDGE.as_bytes()],
+        bump
+    )]
+    bridge_pda: AccountInfo<'info>,
+
+    /// CHECK: custody token account of the NFT, which only exists once it has been locked
+    #[account(
+        mut,
+        seeds = [&origin_nft_seed(transfer.origin_chain, &transfer.origin_contract_address, &transfer.nft_id)],
+        bump
+    )]
+    pub nft_token_account: UncheckedAccount<'info>,
+
+    #[account(
+        init_if_needed,
+        payer = signer,
+        seeds = [NFT_INFO.as_bytes(), &origin_nft_seed(transfer.origin_chain, &transfer.origin_contract_address, &transfer.nft_id)],
+        bump,
+        space = 8 + std::mem::size_of::<NftInfoInBridge>(),
+        constraint = nft_info_account.nonce == transfer.nonce @ BridgeError::InvalidNonce
+    )]
+    pub nft_info_account: Box<Account<'info, NftInfoInBridge>>,
+
+    /// CHECK: mint recorded in the nft info, or the wrapped mint collection_creator derives
+    /// from the origin identifiers on the first entry of a wrapped NFT
+    #[account(mut)]
+    pub nft_mint: UncheckedAccount<'info>,
+
+    /// CHECK: metaplex metadata of the NFT
+    #[account(mut, address = MetadataAccount::find_pda(&nft_mint.key()).0)]
+    pub metadata_account: UncheckedAccount<'info>,
+
+    #[account(
+        seeds=[COLLECTION_MAPPING.as_bytes(), collection_mapping.collection.as_ref(), &[src_chain as u8]],
+        bump = collection_mapping.bump,
+        constraint = !collection_mapping.paused @ BridgeError::BridgePaused
+    )]
+    pub collection_mapping: Box<Account<'info, CollectionMapping>>,
+
+    /// CHECK: recipient of the transfer
+    #[account(mut, address = transfer.receiver)]
+    pub receiver: UncheckedAccount<'info>,
+
+    /// CHECK: receiver's associated token account, created if needed
+    #[account(mut)]
+    pub receiver_token_account: UncheckedAccount<'info>,
+
+    #[account(
+        seeds=[BRIDGE_CONFIG.as_bytes()],
+        bump = bridge_config.bump,
+        constraint = !bridge_config.paused @ BridgeError::BridgePaused
+    )]
+    pub bridge_config: Box<Account<'info, BridgeConfig>>,
+
+    #[account(
+        seeds=[CHAIN_CONFIG.as_bytes(), &[src_chain as u8]],
+        bump = src_chain_config.bump,
+        constraint = src_chain_config.enabled @ BridgeError::ChainNotAllowed,
+        constraint = !src_chain_config.paused @ BridgeError::BridgePaused
+    )]
+    pub src_chain_config: Box<Account<'info, ChainConfig>>,
+
+    // Shared with the other inbound instructions, a transfer is delivered once
+    #[account(
+        init_if_needed,
+        payer = signer,
+        seeds=[PROCESSED_TRANSFER.as_bytes(), &[src_chain as u8], &hash(transfer.bridge_txid.as_bytes()).to_bytes()],
+        bump,
+        space = 8 + ProcessedTransfer::INIT_SPACE,
+        constraint = !processed_transfer.processed @ BridgeError::TransferAlreadyProcessed
+    )]
+    pub processed_transfer: Box<Account<'info, ProcessedTransfer>>,
+
+    // Only needed for guardian attestations
+    #[account(
+        seeds=[GUARDIAN_SET.as_bytes(), &guardian_set.index.to_le_bytes()],
+        bump = guardian_set.bump
+    )]
+    pub guardian_set: Option<Account<'info, GuardianSet>>,
+
+    /// CHECK: instructions sysvar, read for guardian signatures and by Token Metadata
+    #[account(address = sysvar::instructions::ID)]
+    pub instructions: Option<UncheckedAccount<'info>>,
+
+    // Wrapped collections only, the accounts are checked by collection_creator
+    /// CHECK: collection_creator `CollectionInfo`
+    #[account(mut)]
+    pub collection_info_account: Option<UncheckedAccount<'info>>,
+    /// CHECK: collection_creator program
+    #[account(address = COLLECTION_CREATOR_PROGRAM_ID)]
+    pub creator_program: Option<UncheckedAccount<'info>>,
+    /// CHECK:
+    pub creator_config: Option<UncheckedAccount<'info>>,
+    /// CHECK:
+    #[account(mut)]
+    pub collection_mint: Option<UncheckedAccount<'info>>,
+    /// CHECK:
+    #[account(mut)]
+    pub collection_metadata_account: Option<UncheckedAccount<'info>>,
+    /// CHECK:
+    #[account(mut)]
+    pub collection_master_edition: Option<UncheckedAccount<'info>>,
+    /// CHECK:
+    #[account(mut)]
+    pub master_edition: Option<UncheckedAccount<'info>>,
+    pub token_metadata_program: Option<Program<'info, Metadata>>,
+
+    // Any relayer may submit, the transfer is authorised by the attestation
+    #[account(mut)]
+    pub signer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+#[derive(Accounts)]
+#[instruction(origin_chain: ChainId , origin_contract_address:String ,mint_address: Pubkey, nft_id: [u8; 32])]
+pub struct StoreNftInfoInBridge<'info> {
+    #[account(
+        init_if_needed,
+        // seeds = [NFT_INFO.as_bytes(),nft_id.as_bytes(), &[origin_chain as u8], origin_contract_address.as_bytes()],
+        seeds = [NFT_INFO.as_bytes(), &origin_nft_seed(origin_chain, &origin_contract_address, &nft_id)],
+        bump,
+        payer = signer,
+        space = 8 +std::mem::size_of::<NftInfoInBridge>(),
+    )]
+    pub nft_info_account: Box<Account<'info, NftInfoInBridge>>,
+
+    #[account(
+        seeds=[ROLE.as_bytes(), &[Role::Manager as u8], signer.key().as_ref()],
+        bump = signer_role.bump
+    )]
+    pub signer_role: Account<'info, RoleAssignment>,
+
+    #[account(mut)]
+    pub signer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[account]
+pub struct NftInfoInBridge {
+    pub mint_address: Pubkey,
+    pub nonce: u64,
+    pub origin_chain: ChainId,
+}
+
+/// Service fee plus the destination chain fee for `nft_count` NFTs
+pub fn bridge_fee(
+    bridge_config: &BridgeConfig,
+    chain_config: &ChainConfig,
+    nft_count: u64,
+) -> Result<u64> {
+    let extra_nfts = nft_count.saturating_sub(1);
+
+    chain_config
+        .fee_per_nft
+        .checked_mul(extra_nfts)
+        .and_then(|fee| fee.checked_add(chain_config.base_fee))
+        .and_then(|fee| fee.checked_add(bridge_config.service_fee))
+        .ok_or_else(|| error!(BridgeError::InvalidFee))
+}
+
+/// Digest the operator signs for an unlock. Variable length fields are hashed
+/// individually so the encoding stays unambiguous, like `check-signature` in the
+/// Stacks bridge.
+#[allow(clippy::too_many_arguments)]
+pub fn unlock_message_hash(
+    src_chain: ChainId,
+    bridge_txid: &str,
+    origin_chain: ChainId,
+    origin_contract_address: &str,
+    nft_id: [u8; 32],
+    nonce: u64,
+    receiver: &Pubkey,
+    mint: &Pubkey,
+) -> [u8; 32] {
+    keccak::hashv(&[
+        UNLOCK_DOMAIN.as_bytes(),
+        crate::ID.as_ref(),
+        &[src_chain as u8],
+        &keccak::hash(bridge_txid.as_bytes()).to_bytes(),
+        &[origin_chain as u8],
+        &keccak::hash(origin_contract_address.as_bytes()).to_bytes(),
+        &nft_id,
+        &nonce.to_be_bytes(),
+        receiver.as_ref(),
+        mint.as_ref(),
+    ])
+    .to_bytes()
+}
+
+/// Digest the operator signs for a batch unlock, the per NFT lists are hashed as a whole
+#[allow(clippy::too_many_arguments)]
+pub fn unlock_batch_message_hash(
+    src_chain: ChainId,
+    bridge_txid: &str,
+    origin_chain: ChainId,
+    origin_contract_address: &str,
+    nft_ids: &[[u8; 32]],
+    nonces: &[u64],
+    receiver: &Pubkey,
+    mints: &[Pubkey],
+) -> [u8; 32] {
+    let nonces: Vec<u8> = nonces
+        .iter()
+        .flat_map(|nonce| nonce.to_be_bytes())
+        .collect();
+    let mints: Vec<&[u8]> = mints.iter().map(|mint| mint.as_ref()).collect();
+
+    keccak::hashv(&[
+        UNLOCK_BATCH_DOMAIN.as_bytes(),
+        crate::ID.as_ref(),
+        &[src_chain as u8],
+        &keccak::hash(bridge_txid.as_bytes()).to_bytes(),
+        &[origin_chain as u8],
+        &keccak::hash(origin_contract_address.as_bytes()).to_bytes(),
+        &keccak::hash(&nft_ids.concat()).to_bytes(),
+        &keccak::hash(&nonces).to_bytes(),
+        receiver.as_ref(),
+        &keccak::hashv(&mints).to_bytes(),
+    ])
+    .to_bytes()
+}
+
+/// Digest the operator signs for `bridge_in`, the transfer hashed like a batch leaf
+/// followed by the mint it is delivered as
+pub fn bridge_in_message_hash(
+    src_chain: ChainId,
+    transfer: &InboundTransfer,
+    mint: &Pubkey,
+) -> [u8; 32] {
+    keccak::hashv(&[
+        BRIDGE_IN_DOMAIN.as_bytes(),
+        crate::ID.as_ref(),
+        &[src_chain as u8],
+        &inbound_leaf_hash(0, transfer),
+        mint.as_ref(),
+    ])
+    .to_bytes()
+}
+
+/// Message the operator signs to post an inbound batch
+pub fn inbound_batch_message_hash(
+    src_chain: ChainId,
+    batch_id: u64,
+    merkle_root: &[u8; 32],
+    leaf_count: u32,
+) -> [u8; 32] {
+    keccak::hashv(&[
+        INBOUND_BATCH_DOMAIN.as_bytes(),
+        crate::ID.as_ref(),
+        &[src_chain as u8],
+        &batch_id.to_be_bytes(),
+        merkle_root,
+        &leaf_count.to_be_bytes(),
+    ])
+    .to_bytes()
+}
+
+/// Leaf hash of an inbound transfer, the index ties it to its bit in the claimed bitmap
+pub fn inbound_leaf_hash(index: u32, transfer: &InboundTransfer) -> [u8; 32] {
+    inbound_transfer_hash(
+        index,
+        &transfer.bridge_txid,
+        transfer.origin_chain,
+        &transfer.origin_contract_address,
+        &transfer.nft_id,
+        transfer.nonce,
+        &transfer.receiver,
+        &transfer.name,
+        &transfer.symbol,
+        &transfer.uri,
+    )
+}
+
+/// Sorted pair keccak proof, same as OpenZeppelin `MerkleProof.verify` on EVM
+pub fn verify_merkle_proof(leaf: [u8; 32], proof: &[[u8; 32]], root: &[u8; 32]) -> bool {
+    let computed = proof.iter().fold(leaf, |node, sibling| {
+        if node <= *sibling {
+            keccak::hashv(&[&node, sibling]).to_bytes()
+        } else {
+            keccak::hashv(&[sibling, &node]).to_bytes()
+        }
+    });
+
+    computed == *root
+}
+
+fn required<T>(account: &Option<T>) -> Result<&T> {
+    account
+        .as_ref()
+        .ok_or_else(|| error!(ErrorCode::AccountNotEnoughKeys))
+}
+
+fn creator_instruction_data<T: AnchorSerialize>(name: &str, args: &T) -> Result<Vec<u8>> {
+    let mut data = hash(format!("global:{}", name).as_bytes()).to_bytes()[..8].to_vec();
+    args.serialize(&mut data)?;
+
+    Ok(data)
+}
+
+/// Accounts of the collection_creator CPIs. The ones only one of the CPIs uses are `None`
+/// when the instruction never takes that path.
+struct CreatorAccounts<'info> {
+    creator_program: AccountInfo<'info>,
+    creator_config: AccountInfo<'info>,
+    bridge_pda: AccountInfo<'info>,
+    signer: AccountInfo<'info>,
+    collection_mint: AccountInfo<'info>,
+    nft_mint: AccountInfo<'info>,
+    metadata_account: AccountInfo<'info>,
+    master_edition: AccountInfo<'info>,
+    token_account: AccountInfo<'info>,
+    receiver: AccountInfo<'info>,
+    collection_info_account: AccountInfo<'info>,
+    system_program: AccountInfo<'info>,
+    token_program: AccountInfo<'info>,
+    associated_token_program: AccountInfo<'info>,
+    token_metadata_program: AccountInfo<'info>,
+    // create_nft_in_collection only
+    collection_metadata_account: Option<AccountInfo<'info>>,
+    collection_master_edition: Option<AccountInfo<'info>>,
+    rent: Option<AccountInfo<'info>>,
+    // remint_nft_in_collection only
+    instructions: Option<AccountInfo<'info>>,
+}
+
+impl<'info> ClaimWithProof<'info> {
+    fn creator_accounts(&self) -> Result<CreatorAccounts<'info>> {
+        Ok(CreatorAccounts {
+            creator_program: required(&self.creator_program)?.to_account_info(),
+            creator_config: required(&self.creator_config)?.to_account_info(),
+            bridge_pda: self.bridge_pda.to_account_info(),
+            signer: self.signer.to_account_info(),
+            collection_mint: required(&self.collection_mint)?.to_account_info(),
+            nft_mint: self.nft_mint.to_account_info(),
+            metadata_account: self.metadata_account.to_account_info(),
+            master_edition: required(&self.master_edition)?.to_account_info(),
+            token_account: self.receiver_token_account.to_account_info(),
+            receiver: self.receiver.to_account_info(),
+            collection_info_account: required(&self.collection_info_account)?.to_account_info(),
+            system_program: self.system_program.to_account_info(),
+            token_program: self.token_program.to_account_info(),
+            associated_token_program: self.associated_token_program.to_account_info(),
+            token_metadata_program: required(&self.token_metadata_program)?.to_account_info(),
+            collection_metadata_account: self
+                .collection_metadata_account
+                .as_ref()
+                .map(|account| account.to_account_info()),
+            collection_master_edition: self
+                .collection_master_edition
+                .as_ref()
+                .map(|account| account.to_account_info()),
+            rent: Some(self.rent.to_account_info()),
+            instructions: self
+                .instructions
+                .as_ref()
+                .map(|account| account.to_account_info()),
+        })
+    }
+}
+
+impl<'info> UnlockNft<'info> {
+    fn creator_accounts(&self) -> Result<CreatorAccounts<'info>> {
+        Ok(CreatorAccounts {
+            creator_program: required(&self.creator_program)?.to_account_info(),
+            creator_config: required(&self.creator_config)?.to_account_info(),
+            bridge_pda: self.bridge_pda.to_account_info(),
+            signer: self.signer.to_account_info(),
+            collection_mint: required(&self.collection_mint)?.to_account_info(),
+            nft_mint: self.mint_of_token_being_sent.to_account_info(),
+            metadata_account: self.metadata_account.to_account_info(),
+            master_edition: required(&self.master_edition)?.to_account_info(),
+            token_account: self.receiver_token_account.to_account_info(),
+            receiver: self.receiver.to_account_info(),
+            collection_info_account: required(&self.collection_info_account)?.to_account_info(),
+            system_program: self.system_program.to_account_info(),
+            token_program: self.token_program.to_account_info(),
+            associated_token_program: self.associated_token_program.to_account_info(),
+            token_metadata_program: required(&self.token_metadata_program)?.to_account_info(),
+            collection_metadata_account: None,
+            collection_master_edition: None,
+            rent: None,
+            instructions: Some(required(&self.instructions)?.to_account_info()),
+        })
+    }
+}
+
+impl<'info> ExecuteUnlock<'info> {
+    fn creator_accounts(&self) -> Result<CreatorAccounts<'info>> {
+        Ok(CreatorAccounts {
+            creator_program: required(&self.creator_program)?.to_account_info(),
+            creator_config: required(&self.creator_config)?.to_account_info(),
+            bridge_pda: self.bridge_pda.to_account_info(),
+            signer: self.signer.to_account_info(),
+            collection_mint: required(&self.collection_mint)?.to_account_info(),
+            nft_mint: self.mint_of_token_being_sent.to_account_info(),
+            metadata_account: self.metadata_account.to_account_info(),
+            master_edition: required(&self.master_edition)?.to_account_info(),
+            token_account: self.receiver_token_account.to_account_info(),
+            receiver: self.receiver.to_account_info(),
+            collection_info_account: required(&self.collection_info_account)?.to_account_info(),
+            system_program: self.system_program.to_account_info(),
+            token_program: self.token_program.to_account_info(),
+            associated_token_program: self.associated_token_program.to_account_info(),
+            token_metadata_program: required(&self.token_metadata_program)?.to_account_info(),
+            collection_metadata_account: None,
+            collection_master_edition: None,
+            rent: None,
+            instructions: Some(required(&self.instructions)?.to_account_info()),
+        })
+    }
+}
+
+impl<'info> UnlockNftsBatch<'info> {
+    fn creator_accounts(
+        &self,
+        nft_mint: &AccountInfo<'info>,
+        metadata_account: &AccountInfo<'info>,
+        master_edition: &AccountInfo<'info>,
+        token_account: &AccountInfo<'info>,
+    ) -> Result<CreatorAccounts<'info>> {
+        Ok(CreatorAccounts {
+            creator_program: required(&self.creator_program)?.to_account_info(),
+            creator_config: required(&self.creator_config)?.to_account_info(),
+            bridge_pda: self.bridge_pda.to_account_info(),
+            signer: self.signer.to_account_info(),
+            collection_mint: required(&self.collection_mint)?.to_account_info(),
+            nft_mint: nft_mint.clone(),
+            metadata_account: metadata_account.clone(),
+            master_edition: master_edition.clone(),
+            token_account: token_account.clone(),
+            receiver: self.receiver.to_account_info(),
+            collection_info_account: required(&self.collection_info_account)?.to_account_info(),
+            system_program: self.system_program.to_account_info(),
+            token_program: self.token_program.to_account_info(),
+            associated_token_program: self.associated_token_program.to_account_info(),
+            token_metadata_program: required(&self.token_metadata_program)?.to_account_info(),
+            collection_metadata_account: None,
+            collection_master_edition: None,
+            rent: None,
+            instructions: Some(required(&self.instructions)?.to_account_info()),
+        })
+    }
+}
+
+impl<'info> BridgeIn<'info> {
+    fn creator_accounts(&self) -> Result<CreatorAccounts<'info>> {
+        Ok(CreatorAccounts {
+            creator_program: required(&self.creator_program)?.to_account_info(),
+            creator_config: required(&self.creator_config)?.to_account_info(),
+            bridge_pda: self.bridge_pda.to_account_info(),
+            signer: self.signer.to_account_info(),
+            collection_mint: required(&self.collection_mint)?.to_account_info(),
+            nft_mint: self.nft_mint.to_account_info(),
+            metadata_account: self.metadata_account.to_account_info(),
+            master_edition: required(&self.master_edition)?.to_account_info(),
+            token_account: self.receiver_token_account.to_account_info(),
+            receiver: self.receiver.to_account_info(),
+            collection_info_account: required(&self.collection_info_account)?.to_account_info(),
+            system_program: self.system_program.to_account_info(),
+            token_program: self.token_program.to_account_info(),
+            associated_token_program: self.associated_token_program.to_account_info(),
+            token_metadata_program: required(&self.token_metadata_program)?.to_account_info(),
+            collection_metadata_account: self
+                .collection_metadata_account
+                .as_ref()
+                .map(|account| account.to_account_info()),
+            collection_master_edition: self
+                .collection_master_edition
+                .as_ref()
+                .map(|account| account.to_account_info()),
+            rent: Some(self.rent.to_account_info()),
+            instructions: self
+                .instructions
+                .as_ref()
+                .map(|account| account.to_account_info()),
+        })
+    }
+}
+
+/// Accounts of an NFT released from custody, the counterpart of `CreatorAccounts`
+struct CustodyAccounts<'info> {
+    bridge_pda: AccountInfo<'info>,
+    signer: AccountInfo<'info>,
+    nft_token_account: AccountInfo<'info>,
+    nft_mint: AccountInfo<'info>,
+    metadata_account: AccountInfo<'info>,
+    receiver: AccountInfo<'info>,
+    receiver_token_account: AccountInfo<'info>,
+    system_program: AccountInfo<'info>,
+    token_program: AccountInfo<'info>,
+    associated_token_program: AccountInfo<'info>,
+}
+
+impl<'info> UnlockNft<'info> {
+    fn custody_accounts(&self) -> CustodyAccounts<'info> {
+        CustodyAccounts {
+            bridge_pda: self.bridge_pda.to_account_info(),
+            signer: self.signer.to_account_info(),
+            nft_token_account: self.nft_token_account.to_account_info(),
+            nft_mint: self.mint_of_token_being_sent.to_account_info(),
+            metadata_account: self.metadata_account.to_account_info(),
+            receiver: self.receiver.to_account_info(),
+            receiver_token_account: self.receiver_token_account.to_account_info(),
+            system_program: self.system_program.to_account_info(),
+            token_program: self.token_program.to_account_info(),
+            associated_token_program: self.associated_token_program.to_account_info(),
+        }
+    }
+}
+
+impl<'info> ExecuteUnlock<'info> {
+    fn custody_accounts(&self) -> CustodyAccounts<'info> {
+        CustodyAccounts {
+            bridge_pda: self.bridge_pda.to_account_info(),
+            signer: self.signer.to_account_info(),
+            nft_token_account: self.nft_token_account.to_account_info(),
+            nft_mint: self.mint_of_token_being_sent.to_account_info(),
+            metadata_account: self.metadata_account.to_account_info(),
+            receiver: self.receiver.to_account_info(),
+            receiver_token_account: self.receiver_token_account.to_account_info(),
+            system_program: self.system_program.to_account_info(),
+            token_program: self.token_program.to_account_info(),
+            associated_token_program: self.associated_token_program.to_account_info(),
+        }
+    }
+}
+
+impl<'info> UnlockNftsBatch<'info> {
+    fn custody_accounts(
+        &self,
+        nft_mint: &AccountInfo<'info>,
+        metadata_account: &AccountInfo<'info>,
+        nft_token_account: &AccountInfo<'info>,
+        receiver_token_account: &AccountInfo<'info>,
+    ) -> CustodyAccounts<'info> {
+        CustodyAccounts {
+            bridge_pda: self.bridge_pda.to_account_info(),
+            signer: self.signer.to_account_info(),
+            nft_token_account: nft_token_account.clone(),
+            nft_mint: nft_mint.clone(),
+            metadata_account: metadata_account.clone(),
+            receiver: self.receiver.to_account_info(),
+            receiver_token_account: receiver_token_account.clone(),
+            system_program: self.system_program.to_account_info(),
+            token_program: self.token_program.to_account_info(),
+            associated_token_program: self.associated_token_program.to_account_info(),
+        }
+    }
+}
+
+impl<'info> ClaimWithProof<'info> {
+    fn custody_accounts(&self) -> CustodyAccounts<'info> {
+        CustodyAccounts {
+            bridge_pda: self.bridge_pda.to_account_info(),
+            signer: self.signer.to_account_info(),
+            nft_token_account: self.nft_token_account.to_account_info(),
+            nft_mint: self.nft_mint.to_account_info(),
+            metadata_account: self.metadata_account.to_account_info(),
+            receiver: self.receiver.to_account_info(),
+            receiver_token_account: self.receiver_token_account.to_account_info(),
+            system_program: self.system_program.to_account_info(),
+            token_program: self.token_program.to_account_info(),
+            associated_token_program: self.associated_token_program.to_account_info(),
+        }
+    }
+}
+
+impl<'info> BridgeIn<'info> {
+    fn custody_accounts(&self) -> CustodyAccounts<'info> {
+        CustodyAccounts {
+            bridge_pda: self.bridge_pda.to_account_info(),
+            signer: self.signer.to_account_info(),
+            nft_token_account: self.nft_token_account.to_account_info(),
+            nft_mint: self.nft_mint.to_account_info(),
+            metadata_account: self.metadata_account.to_account_info(),
+            receiver: self.receiver.to_account_info(),
+            receiver_token_account: self.receiver_token_account.to_account_info(),
+            system_program: self.system_program.to_account_info(),
+            token_program: self.token_program.to_account_info(),
+            associated_token_program: self.associated_token_program.to_account_info(),
+        }
+    }
+}
+
+/// Delivers an inbound transfer from on-chain state: an NFT held in custody is unlocked,
+/// a wrapped NFT is minted again once known or created on its first entry, and anything
+/// else is rejected. Returns whether the NFT was minted.
+#[allow(clippy::too_many_arguments)]
+fn deliver_inbound<'info>(
+    custody: &CustodyAccounts<'info>,
+    creator_accounts: impl FnOnce() -> Result<CreatorAccounts<'info>>,
+    collection: &Pubkey,
+    collection_info_account: &Option<UncheckedAccount<'info>>,
+    known_mint: Pubkey,
+    bridge_bump: u8,
+    src_chain: ChainId,
+    transfer: &InboundTransfer,
+) -> Result<bool> {
+    let nft_mint = custody.nft_mint.key();
+    if known_mint == Pubkey::default() && !custody_holds(&custody.nft_token_account, &nft_mint)? {
+        // First entry, only a wrapped NFT can be created
+        require!(
+            is_wrapped_collection(
+                collection,
+                collection_info_account,
+                transfer.origin_chain,
+                &transfer.origin_contract_address,
+            )?,
+            BridgeError::NftNotInCustody
+        );
+        check_wrapped_mint(
+            &nft_mint,
+            transfer.origin_chain,
+            &transfer.origin_contract_address,
+            &transfer.nft_id,
+        )?;
+        mint_in_collection(&creator_accounts()?, bridge_bump, src_chain, transfer)?;
+
+        return Ok(true);
+    }
+
+    verify_collection(&custody.metadata_account, collection)?;
+    release_nft(
+        custody,
+        creator_accounts,
+        collection,
+        collection_info_account,
+        bridge_bump,
+        transfer.origin_chain,
+        &transfer.origin_contract_address,
+    )
+}
+
+/// Sends an NFT back to its receiver: out of custody when it is held there, otherwise a
+/// wrapped NFT burned when it left Solana is minted again. Solana NFTs only ever leave
+/// through custody. Returns whether the NFT was minted.
+fn release_nft<'info>(
+    custody: &CustodyAccounts<'info>,
+    creator_accounts: impl FnOnce() -> Result<CreatorAccounts<'info>>,
+    collection: &Pubkey,
+    collection_info_account: &Option<UncheckedAccount<'info>>,
+    bridge_bump: u8,
+    origin_chain: ChainId,
+    origin_contract_address: &str,
+) -> Result<bool> {
+    if custody_holds(&custody.nft_token_account, &custody.nft_mint.key())? {
+        create_idempotent(CpiContext::new(
+            custody.associated_token_program.clone(),
+            Create {
+                payer: custody.signer.clone(),
+                associated_token: custody.receiver_token_account.clone(),
+                authority: custody.receiver.clone(),
+                mint: custody.nft_mint.clone(),
+                system_program: custody.system_program.clone(),
+                token_program: custody.token_program.clone(),
+            },
+        ))?;
+
+        let seeds = &[BRIDGE.as_bytes(), &[bridge_bump]];
+        let signer = &[&seeds[..]];
+
+        anchor_spl::token::transfer(
+            CpiContext::new_with_signer(
+                custody.token_program.clone(),
+                Transfer {
+                    from: custody.nft_token_account.clone(),
+                    to: custody.receiver_token_account.clone(),
+                    authority: custody.bridge_pda.clone(),
+                },
+                signer,
+            ),
+            1,
+        )?;
+
+        return Ok(false);
+    }
+
+    require!(
+        is_wrapped_collection(
+            collection,
+            collection_info_account,
+            origin_chain,
+            origin_contract_address,
+        )?,
+        BridgeError::NftNotInCustody
+    );
+    remint_in_collection(
+        &creator_accounts()?,
+        bridge_bump,
+        origin_chain,
+        origin_contract_address,
+    )?;
+
+    Ok(true)
+}
+
+/// Mints and verifies a wrapped NFT through collection_creator, signing as the bridge PDA
+fn mint_in_collection(
+    accounts: &CreatorAccounts,
+    bridge_bump: u8,
+    src_chain: ChainId,
+    transfer: &InboundTransfer,
+) -> Result<Pubkey> {
+    let collection_metadata_account = required(&accounts.collection_metadata_account)?;
+    let collection_master_edition = required(&accounts.collection_master_edition)?;
+    let rent = required(&accounts.rent)?;
+
+    let seeds: &[&[u8]] = &[BRIDGE.as_bytes(), &[bridge_bump]];
+    // Optional accounts that are not passed are replaced by the program id
+    let none = AccountMeta::new_readonly(COLLECTION_CREATOR_PROGRAM_ID, false);
+
+    let create = Instruction {
+        program_id: COLLECTION_CREATOR_PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new_readonly(accounts.creator_config.key(), false),
+            none.clone(),
+            none.clone(),
+            none.clone(),
+            AccountMeta::new_readonly(accounts.bridge_pda.key(), true),
+            AccountMeta::new(accounts.signer.key(), true),
+            AccountMeta::new(accounts.collection_mint.key(), false),
+            AccountMeta::new(collection_metadata_account.key(), false),
+            AccountMeta::new(collection_master_edition.key(), false),
+            AccountMeta::new(accounts.nft_mint.key(), false),
+            AccountMeta::new(accounts.metadata_account.key(), false),
+            AccountMeta::new(accounts.master_edition.key(), false),
+            AccountMeta::new(accounts.token_account.key(), false),
+            AccountMeta::new(accounts.receiver.key(), false),
+            AccountMeta::new(accounts.collection_info_account.key(), false),
+            AccountMeta::new_readonly(accounts.system_program.key(), false),
+            AccountMeta::new_readonly(accounts.token_program.key(), false),
+            AccountMeta::new_readonly(accounts.associated_token_program.key(), false),
+            AccountMeta::new_readonly(accounts.token_metadata_program.key(), false),
+            AccountMeta::new_readonly(rent.key(), false),
+        ],
+        data: creator_instruction_data(
+            "create_nft_in_collection",
+            &(
+                &transfer.uri,
+                &transfer.name,
+                &transfer.symbol,
+                &transfer.origin_chain,
+                &transfer.origin_contract_address,
+                &transfer.nft_id,
+                src_chain,
+                &transfer.bridge_txid,
+                transfer.nonce,
+            ),
+        )?,
+    };
+
+    let verify = Instruction {
+        program_id: COLLECTION_CREATOR_PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new_readonly(accounts.creator_config.key(), false),
+            none,
+            AccountMeta::new_readonly(accounts.bridge_pda.key(), true),
+            AccountMeta::new(accounts.signer.key(), true),
+            AccountMeta::new(accounts.collection_mint.key(), false),
+            AccountMeta::new(collection_metadata_account.key(), false),
+            AccountMeta::new(collection_master_edition.key(), false),
+            AccountMeta::new_readonly(accounts.nft_mint.key(), false),
+            AccountMeta::new(accounts.metadata_account.key(), false),
+            AccountMeta::new(accounts.master_edition.key(), false),
+            AccountMeta::new_readonly(accounts.token_account.key(), false),
+            AccountMeta::new(accounts.receiver.key(), false),
+            AccountMeta::new(accounts.collection_info_account.key(), false),
+            AccountMeta::new_readonly(accounts.system_program.key(), false),
+            AccountMeta::new_readonly(accounts.token_program.key(), false),
+            AccountMeta::new_readonly(accounts.associated_token_program.key(), false),
+            AccountMeta::new_readonly(accounts.token_metadata_program.key(), false),
+            AccountMeta::new_readonly(rent.key(), false),
+        ],
+        data: creator_instruction_data(
+            "verify_nft_in_collection",
+            &(&transfer.origin_chain, &transfer.origin_contract_address),
+        )?,
+    };
+
+    let account_infos = [
+        accounts.creator_program.clone(),
+        accounts.creator_config.clone(),
+        accounts.bridge_pda.clone(),
+        accounts.signer.clone(),
+        accounts.collection_mint.clone(),
+        collection_metadata_account.clone(),
+        collection_master_edition.clone(),
+        accounts.nft_mint.clone(),
+        accounts.metadata_account.clone(),
+        accounts.master_edition.clone(),
+        accounts.token_account.clone(),
+        accounts.receiver.clone(),
+        accounts.collection_info_account.clone(),
+        accounts.system_program.clone(),
+        accounts.token_program.clone(),
+        accounts.associated_token_program.clone(),
+        accounts.token_metadata_program.clone(),
+        rent.clone(),
+    ];
+    invoke_signed(&create, &account_infos, &[seeds])?;
+    invoke_signed(&verify, &account_infos, &[seeds])?;
+
+    Ok(accounts.nft_mint.key())
+}
+
+/// Mints a wrapped NFT burned when it left Solana back to the receiver
+fn remint_in_collection(
+    accounts: &CreatorAccounts,
+    bridge_bump: u8,
+    origin_chain: ChainId,
+    origin_contract_address: &str,
+) -> Result<()> {
+    let instructions = required(&accounts.instructions)?;
+
+    let seeds: &[&[u8]] = &[BRIDGE.as_bytes(), &[bridge_bump]];
+
+    let remint = Instruction {
+        program_id: COLLECTION_CREATOR_PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new_readonly(accounts.creator_config.key(), false),
+            AccountMeta::new_readonly(accounts.bridge_pda.key(), true),
+            AccountMeta::new(accounts.signer.key(), true),
+            AccountMeta::new_readonly(accounts.collection_mint.key(), false),
+            AccountMeta::new(accounts.nft_mint.key(), false),
+            AccountMeta::new_readonly(accounts.metadata_account.key(), false),
+            AccountMeta::new(accounts.master_edition.key(), false),
+            AccountMeta::new(accounts.token_account.key(), false),
+            AccountMeta::new_readonly(accounts.receiver.key(), false),
+            AccountMeta::new_readonly(accounts.collection_info_account.key(), false),
+            AccountMeta::new_readonly(instructions.key(), false),
+            AccountMeta::new_readonly(accounts.system_program.key(), false),
+            AccountMeta::new_readonly(accounts.token_program.key(), false),
+            AccountMeta::new_readonly(accounts.associated_token_program.key(), false),
+            AccountMeta::new_readonly(accounts.token_metadata_program.key(), false),
+        ],
+        data: creator_instruction_data(
+            "remint_nft_in_collection",
+            &(&origin_chain, origin_contract_address),
+        )?,
+    };
+
+    let account_infos = [
+        accounts.creator_program.clone(),
+        accounts.creator_config.clone(),
+        accounts.bridge_pda.clone(),
+        accounts.signer.clone(),
+        accounts.collection_mint.clone(),
+        accounts.nft_mint.clone(),
+        accounts.metadata_account.clone(),
+        accounts.master_edition.clone(),
+        accounts.token_account.clone(),
+        accounts.receiver.clone(),
+        accounts.collection_info_account.clone(),
+        instructions.clone(),
+        accounts.system_program.clone(),
+        accounts.token_program.clone(),
+        accounts.associated_token_program.clone(),
+        accounts.token_metadata_program.clone(),
+    ];
+    invoke_signed(&remint, &account_infos, &[seeds])?;
+
+    Ok(())
+}
+
+/// Whether the custody token account exists and holds the NFT of `mint`
+fn custody_holds(nft_token_account: &AccountInfo, mint: &Pubkey) -> Result<bool> {
+    if *nft_token_account.owner != Token::id() {
+        return Ok(false);
+    }
+
+    let custody = TokenAccount::try_deserialize(&mut &nft_token_account.try_borrow_data()?[..])?;
+    Ok(custody.mint == *mint && custody.amount == 1)
+}
+
+/// Whether `collection` is the collection_creator collection wrapping an origin collection
+/// of another chain. Such NFTs are burned when they leave Solana and minted on return.
+fn is_wrapped_collection(
+    collection: &Pubkey,
+    collection_info_account: &Option<UncheckedAccount>,
+    origin_chain: ChainId,
+    origin_contract_address: &str,
+) -> Result<bool> {
+    let (wrapped_collection, _) = Pubkey::find_program_address(
+        &[
+            CREATOR_COLLECTION.as_bytes(),
+            &origin_collection_seed(origin_chain, origin_contract_address),
+        ],
+        &COLLECTION_CREATOR_PROGRAM_ID,
+    );
+    if *collection != wrapped_collection {
+        return Ok(false);
+    }
+
+    let collection_info = load_collection_info(
+        required(collection_info_account)?,
+        &COLLECTION_CREATOR_PROGRAM_ID,
+        origin_chain,
+        origin_contract_address,
+    )?;
+
+    Ok(collection_info.origin_chain != ChainId::Solana)
+}
+
+/// A wrapped NFT only stands for the origin NFT its mint was created for, otherwise burning
+/// one token could release another NFT on its origin chain
+fn check_wrapped_mint(
+    mint: &Pubkey,
+    origin_chain: ChainId,
+    origin_contract_address: &str,
+    nft_id: &[u8; 32],
+) -> Result<()> {
+    require_keys_eq!(
+        *mint,
+        wrapped_nft_mint(
+            origin_chain,
+            origin_contract_address,
+            nft_id,
+            &COLLECTION_CREATOR_PROGRAM_ID,
+        ),
+        BridgeError::WrappedMintMismatch
+    );
+
+    Ok(())
+}
+
+/// Creates the `seeds` PDA of this program unless it already exists, the counterpart of
+/// `init_if_needed` for accounts passed through `remaining_accounts`. Returns whether the
+/// account was created.
+fn init_pda_account<'info>(
+    payer: &AccountInfo<'info>,
+    account: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    seeds: &[&[u8]],
+    space: usize,
+    owner: &Pubkey,
+) -> Result<bool> {
+    let (address, bump) = Pubkey::find_program_address(seeds, &crate::ID);
+    require_keys_eq!(account.key(), address, ErrorCode::ConstraintSeeds);
+    if *account.owner != System::id() {
+        return Ok(false);
+    }
+
+    let bump = [bump];
+    let mut signer_seeds = seeds.to_vec();
+    signer_seeds.push(&bump);
+    let signer = &[&signer_seeds[..]];
+
+    let rent = Rent::get()?.minimum_balance(space);
+    let lamports = account.lamports();
+    if lamports == 0 {
+        invoke_signed(
+            &anchor_lang::solana_program::system_instruction::create_account(
+                payer.key,
+                account.key,
+                rent,
+                space as u64,
+                owner,
+            ),
+            &[payer.clone(), account.clone(), system_program.clone()],
+            signer,
+        )?;
+    } else {
+        // The address was funded up front, create_account would fail on it
+        if lamports < rent {
+            invoke(
+                &anchor_lang::solana_program::system_instruction::transfer(
+                    payer.key,
+                    account.key,
+                    rent - lamports,
+                ),
+                &[payer.clone(), account.clone(), system_program.clone()],
+            )?;
+        }
+        invoke_signed(
+            &anchor_lang::solana_program::system_instruction::allocate(account.key, space as u64),
+            &[account.clone(), system_program.clone()],
+            signer,
+        )?;
+        invoke_signed(
+            &anchor_lang::solana_program::system_instruction::assign(account.key, owner),
+            &[account.clone(), system_program.clone()],
+            signer,
+        )?;
+    }
+
+    Ok(true)
+}
+
+/// Checks the NFT's metadata carries `collection` as its verified collection
+pub fn verify_collection(metadata_account: &AccountInfo, collection: &Pubkey) -> Result<()> {
+    let metadata = MetadataAccount::safe_deserialize(&metadata_account.try_borrow_data()?)
+        .map_err(|_| error!(BridgeError::CollectionNotAllowed))?;
+    require!(
+        metadata
+            .collection
+            .is_some_and(|c| c.verified && c.key == *collection),
+        BridgeError::CollectionNotAllowed
+    );
+
+    Ok(())
+}
+
+/// Checks the operator or guardian attestation of an inbound transfer
+#[allow(clippy::too_many_arguments)]
+pub fn verify_attestation(
+    attestation: &Attestation,
+    bridge_config: &BridgeConfig,
+    guardian_set: Option<&Account<GuardianSet>>,
+    instructions: Option<&UncheckedAccount>,
+    src_chain: ChainId,
+    bridge_txid: &str,
+    origin_chain: ChainId,
+    origin_contract_address: &str,
+    nft_id: [u8; 32],
+    nonce: u64,
+    receiver: &Pubkey,
+    mint: &Pubkey,
+) -> Result<()> {
+    match attestation {
+        Attestation::Operator { signature } => {
+            let message_hash = unlock_message_hash(
+                src_chain,
+                bridge_txid,
+                origin_chain,
+                origin_contract_address,
+                nft_id,
+                nonce,
+                receiver,
+                mint,
+            );
+            verify_operator_signature(bridge_config, &message_hash, signature)
+        }
+        Attestation::Guardians => {
+            let guardian_set =
+                guardian_set.ok_or_else(|| error!(BridgeError::InvalidGuardianSet))?;
+            let instructions =
+                instructions.ok_or_else(|| error!(BridgeError::InvalidGuardianSignature))?;
+
+            let message = guardian_message(
+                &crate::ID,
+                src_chain,
+                mint,
+                receiver,
+                bridge_txid,
+                &nft_id,
+                nonce,
+            );
+            verify_guardian_signatures(instructions, guardian_set, &message)
+        }
+    }
+}
+
+/// Batch counterpart of `verify_attestation`, one attestation covers every NFT
+#[allow(clippy::too_many_arguments)]
+pub fn verify_batch_attestation(
+    attestation: &Attestation,
+    bridge_config: &BridgeConfig,
+    guardian_set: Option<&Account<GuardianSet>>,
+    instructions: Option<&UncheckedAccount>,
+    src_chain: ChainId,
+    bridge_txid: &str,
+    origin_chain: ChainId,
+    origin_contract_address: &str,
+    nft_ids: &[[u8; 32]],
+    nonces: &[u64],
+    receiver: &Pubkey,
+    mints: &[Pubkey],
+) -> Result<()> {
+    match attestation {
+        Attestation::Operator { signature } => {
+            let message_hash = unlock_batch_message_hash(
+                src_chain,
+                bridge_txid,
+                origin_chain,
+                origin_contract_address,
+                nft_ids,
+                nonces,
+                receiver,
+                mints,
+            );
+            verify_operator_signature(bridge_config, &message_hash, signature)
+        }
+        Attestation::Guardians => {
+            let guardian_set =
+                guardian_set.ok_or_else(|| error!(BridgeError::InvalidGuardianSet))?;
+            let instructions =
+                instructions.ok_or_else(|| error!(BridgeError::InvalidGuardianSignature))?;
+
+            let message = guardian_batch_message(
+                &crate::ID,
+                src_chain,
+                mints,
+                receiver,
+                bridge_txid,
+                nft_ids,
+                nonces,
+            );
+            verify_guardian_signatures(instructions, guardian_set, &message)
+        }
+    }
+}
+
+/// `verify_attestation` of a `bridge_in` transfer
+pub fn verify_inbound_attestation(
+    attestation: &Attestation,
+    bridge_config: &BridgeConfig,
+    guardian_set: Option<&Account<GuardianSet>>,
+    instructions: Option<&UncheckedAccount>,
+    src_chain: ChainId,
+    transfer: &InboundTransfer,
+    mint: &Pubkey,
+) -> Result<()> {
+    match attestation {
+        Attestation::Operator { signature } => {
+            let message_hash = bridge_in_message_hash(src_chain, transfer, mint);
+            verify_operator_signature(bridge_config, &message_hash, signature)
+        }
+        Attestation::Guardians => {
+            let guardian_set =
+                guardian_set.ok_or_else(|| error!(BridgeError::InvalidGuardianSet))?;
+            let instructions =
+                instructions.ok_or_else(|| error!(BridgeError::InvalidGuardianSignature))?;
+
+            // Same transfer hash as the operator message, the name, symbol and uri are
+            // attested too
+            let message = guardian_inbound_message(
+                &crate::ID,
+                src_chain,
+                mint,
+                &inbound_leaf_hash(0, transfer),
+            );
+            verify_guardian_signatures(instructions, guardian_set, &message)
+        }
+    }
+}
+
+/// Checks a 65 byte `r || s || v` signature against the configured operator key
+pub fn verify_operator_signature(
+    bridge_config: &BridgeConfig,
+    message_hash: &[u8; 32],
+    signature: &[u8; 65],
+) -> Result<()> {
+    // Accept both raw (0/1) and Ethereum style (27/28) recovery ids
+    let recovery_id = match signature[64] {
+        27 | 28 => signature[64] - 27,
+        v => v,
+    };
+
+    let recovered = secp256k1_recover(message_hash, recovery_id, &signature[..64])
+        .map_err(|_| error!(BridgeError::SenderNotBridgeManager))?;
+    require!(
+        recovered.to_bytes() == bridge_config.operator_public_key,
+        BridgeError::SenderNotBridgeManager
+    );
+
+    Ok(())
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct BridgeConfig {
+    pub admin: Pubkey,
+    // Relayer key of the operator, its unlocks are authorised by operator_public_key
+    pub operator: Pubkey,
+    pub treasury: Pubkey,
+    // Uncompressed secp256k1 key (without the 0x04 prefix) that signs unlocks
+    pub operator_public_key: [u8; 64],
+    pub guardian_set_index: u32,
+    // Seconds the replaced guardian set is still accepted after a rotation
+    pub guardian_set_grace_period: i64,
+    pub service_fee: u64,
+    // Most NFTs a single batch lock or unlock may carry
+    pub max_batch_size: u8,
+    pub paused: bool,
+    pub bump: u8,
+}
+
+/// Registry entry for a remote chain, the equivalent of EVM `s_bridgeManagers`
+#[account]
+#[derive(InitSpace)]
+pub struct ChainConfig {
+    pub chain: ChainId,
+    // Bridge manager contract on the remote chain
+    #[max_len(128)]
+    pub bridge_manager: String,
+    pub address_format: AddressFormat,
+    // Chain selector the EVM BridgeManager uses for this chain, zero if it is not on CCIP
+    pub ccip_chain_selector: u64,
+    pub enabled: bool,
+    pub paused: bool,
+    pub base_fee: u64,
+    // Added for every NFT after the first one in a batch
+    pub fee_per_nft: u64,
+    pub bump: u8,
+}
+
+/// Remote collection for a verified Solana collection, the equivalent of EVM `s_nftCollectionMapping`
+#[account]
+#[derive(InitSpace)]
+pub struct CollectionMapping {
+    // Verified metaplex collection mint
+    pub collection: Pubkey,
+    pub dst_chain: ChainId,
+    #[max_len(128)]
+    pub dst_collection: String,
+    pub paused: bool,
+    // Seconds a proposed unlock waits before it can be executed, zero to unlock directly
+    pub challenge_period: u32,
+    pub bump: u8,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq, InitSpace)]
+pub enum AddressFormat {
+    Evm,
+    Stacks,
+    Flow,
+}
+
+/// Holds collected bridge fees until they are withdrawn to `BridgeConfig.treasury`
+#[account]
+#[derive(InitSpace)]
+pub struct Treasury {
+    pub bump: u8,
+}
+
+/// Who vouches for an inbound transfer
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub enum Attestation {
+    // secp256k1 signature from the bridge operator
+    Operator { signature: [u8; 65] },
+    // Ed25519 guardian signatures in the preceding instruction
+    Guardians,
+}
+
+/// Roles mirroring the EVM BridgeManager. The admin role is held by
+/// `BridgeConfig.admin` and moved with `set_admin`. There is no minter role, inbound
+/// transfers are authorised by their attestation and any relayer may submit them.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq, InitSpace)]
+pub enum Role {
+    Manager,
+    Pauser,
+    // Can veto optimistic unlocks during their challenge window
+    Watcher,
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct RoleAssignment {
+    pub role: Role,
+    pub member: Pubkey,
+    pub bump: u8,
+}
+
+/// Unlock proposed by propose_unlock, waiting out the collection's challenge period
+#[account]
+#[derive(InitSpace)]
+pub struct PendingUnlock {
+    pub proposer: Pubkey,
+    pub receiver: Pubkey,
+    pub mint: Pubkey,
+    // Mapped collection of the NFT, its pause switch still applies at execution
+    pub collection: Pubkey,
+    pub origin_chain: ChainId,
+    #[max_len(128)]
+    pub origin_contract_address: String,
+    pub nft_id: [u8; 32],
+    #[max_len(128)]
+    pub coll_id: String,
+    pub src_chain: ChainId,
+    #[max_len(128)]
+    pub src_address: String,
+    #[max_len(128)]
+    pub dst_address: String,
+    #[max_len(128)]
+    pub bridge_txid: String,
+    pub nonce: u64,
+    pub executable_after: i64,
+    pub bump: u8,
+}
+
+/// Merkle root of inbound transfers posted by the operator, claimed one leaf at a time
+#[account]
+#[derive(InitSpace)]
+pub struct InboundBatch {
+    pub src_chain: ChainId,
+    pub batch_id: u64,
+    pub merkle_root: [u8; 32],
+    pub leaf_count: u32,
+    // One bit per leaf, set once claimed
+    #[max_len(MAX_INBOUND_BATCH_SIZE / 8)]
+    pub claimed: Vec<u8>,
+    pub bump: u8,
+}
+
+/// Leaf of an inbound batch
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct InboundTransfer {
+    pub origin_chain: ChainId,
+    pub origin_contract_address: String,
+    pub nft_id: [u8; 32],
+    pub receiver: Pubkey,
+    pub bridge_txid: String,
+    pub nonce: u64,
+    // Metadata for NFTs minted by collection_creator, empty for unlocks
+    pub name: String,
+    pub symbol: String,
+    pub uri: String,
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct ProcessedTransfer {
+    pub processed: bool,
+    // Set by veto_unlock, only the admin can free the bridge txid again
+    pub vetoed: bool,
+    pub mint: Pubkey,
+    pub receiver: Pubkey,
+    pub processed_at: i64,
+    pub bump: u8,
+    // Every mint of a batch unlock, `mint` is the first one. Empty for single transfers,
+    // the batch sizes the account for its mints.
+    #[max_len(0)]
+    pub mints: Vec<Pubkey>,
+}
+
+#[event]
+pub struct InboundBatchPosted {
+    pub src_chain: ChainId,
+    pub batch_id: u64,
+    pub merkle_root: [u8; 32],
+    pub leaf_count: u32,
+}
+
+#[event]
+pub struct InboundTransferClaimed {
+    pub src_chain: ChainId,
+    pub batch_id: u64,
+    pub index: u32,
+    pub receiver: Pubkey,
+    pub mint: Pubkey,
+    pub origin_chain: ChainId,
+    pub origin_contract_address: String,
+    pub nft_id: [u8; 32],
+    pub bridge_txid: String,
+}
+
+#[event]
+pub struct UnlockProposed {
+    pub src_chain: ChainId,
+    pub bridge_txid: String,
+    pub receiver: Pubkey,
+    pub mint: Pubkey,
+    pub nft_id: [u8; 32],
+    pub executable_after: i64,
+}
+
+#[event]
+pub struct UnlockVetoed {
+    pub src_chain: ChainId,
+    pub bridge_txid: String,
+    pub receiver: Pubkey,
+    pub mint: Pubkey,
+    pub watcher: Pubkey,
+}
+
+#[event]
+pub struct GuardianSetRotated {
+    pub previous_index: u32,
+    pub previous_expiration_time: i64,
+    pub new_index: u32,
+    pub keys: Vec<Pubkey>,
+    pub threshold: u8,
+}
+
+/// Mirrors the EVM `NFTLocked` event
+#[event]
+pub struct NftLocked {
+    pub owner: Pubkey,
+    pub mint: Pubkey,
+    pub origin_chain: ChainId,
+    pub origin_contract_address: String,
+    // Big-endian, so an EVM uint256 token id maps over unchanged
+    pub nft_id: [u8; 32],
+    pub coll_id: String,
+    pub src_address: String,
+    pub dst_chain: ChainId,
+    pub dst_address: String,
+    pub fee: u64,
+    pub nonce: u64,
+}
+
+/// `NftLocked` of a batch lock, one fee and one entry per NFT
+#[event]
+pub struct NftsLocked {
+    pub owner: Pubkey,
+    pub mints: Vec<Pubkey>,
+    pub origin_chain: ChainId,
+    pub origin_contract_address: String,
+    pub nft_ids: Vec<[u8; 32]>,
+    pub coll_id: String,
+    pub src_address: String,
+    pub dst_chain: ChainId,
+    pub dst_address: String,
+    pub fee: u64,
+    pub nonces: Vec<u64>,
+}
+
+/// Mirrors the EVM `NFTUnlocked` event
+#[event]
+pub struct NftUnlocked {
+    pub src_chain: ChainId,
+    pub src_address: String,
+    pub receiver: Pubkey,
+    pub mint: Pubkey,
+    pub origin_chain: ChainId,
+    pub origin_contract_address: String,
+    pub nft_id: [u8; 32],
+    pub coll_id: String,
+    pub dst_address: String,
+    pub bridge_txid: String,
+    pub nonce: u64,
+}
+
+/// Inbound transfer delivered by `bridge_in`, unlocked from custody or minted
+#[event]
+pub struct NftBridgedIn {
+    pub src_chain: ChainId,
+    pub receiver: Pubkey,
+    pub mint: Pubkey,
+    pub origin_chain: ChainId,
+    pub origin_contract_address: String,
+    pub nft_id: [u8; 32],
+    pub bridge_txid: String,
+    pub nonce: u64,
+    pub minted: bool,
+}
+
+/// `NftUnlocked` of a batch unlock, one bridge txid and one entry per NFT
+#[event]
+pub struct NftsUnlocked {
+    pub src_chain: ChainId,
+    pub src_address: String,
+    pub receiver: Pubkey,
+    pub mints: Vec<Pubkey>,
+    pub origin_chain: ChainId,
+    pub origin_contract_address: String,
+    pub nft_ids: Vec<[u8; 32]>,
+    pub coll_id: String,
+    pub dst_address: String,
+    pub bridge_txid: String,
+    pub nonces: Vec<u64>,
+}
+
+/// Codes 6000-6006 follow the EVM BridgeManager custom errors in declaration order
+/// (`BridgeManager_ChainNotAllowed` ... `BridgeManager_InvalidNFTLength`), and the codes
+/// up to `InvalidGuardianSignature` are the same in every program of the bridge, so
+/// clients can decode a failed transfer the same way whichever side rejected it.
+/// Only ever append new variants.
+#[error_code]
+pub enum BridgeError {
+    #[msg("Chain is not registered or has been disabled")]
+    ChainNotAllowed,
+    #[msg("Collection is not mapped to the destination chain")]
+    CollectionNotAllowed,
+    #[msg("Attestation is not signed by the bridge operator")]
+    SenderNotBridgeManager,
+    #[msg("Fee does not match the configured bridge fee")]
+    InvalidFee,
+    // Fees are never refunded on Solana, kept so the codes line up with EVM
+    #[msg("Fee refund failed")]
+    RefundFailed,
+    #[msg("Sender does not hold the NFT")]
+    InvalidNftOwner,
+    #[msg("Number of NFTs is zero or above the batch limit")]
+    InvalidNftLength,
+    #[msg("Transfer with this bridge txid has already been processed")]
+    TransferAlreadyProcessed,
+    #[msg("Bridge is paused")]
+    BridgePaused,
+    #[msg("Destination address is not valid for the destination chain")]
+    InvalidDestinationAddress,
+    #[msg("Signer is not authorized to perform this action")]
+    Unauthorized,
+    #[msg("Guardian set is invalid")]
+    InvalidGuardianSet,
+    #[msg("Guardian set has expired")]
+    GuardianSetExpired,
+    #[msg("Guardian signatures are missing or below the threshold")]
+    InvalidGuardianSignature,
+    #[msg("Nonce does not match the bridge nonce stored for this NFT")]
+    InvalidNonce,
+    #[msg("Collection requires unlocks to go through propose_unlock")]
+    ChallengeWindowRequired,
+    #[msg("Challenge window has not elapsed yet")]
+    ChallengeWindowOpen,
+    #[msg("Challenge window has already elapsed")]
+    ChallengeWindowClosed,
+    #[msg("Inbound batch is empty, too large or the leaf index is out of range")]
+    InvalidInboundBatch,
+    #[msg("Merkle proof does not match the inbound batch root")]
+    InvalidMerkleProof,
+    #[msg("NFT is not in custody and its collection is not a wrapped collection")]
+    NftNotInCustody,
+    #[msg("Transfer with this bridge txid has been vetoed")]
+    TransferVetoed,
+    #[msg("Mint is not the wrapped mint of this origin NFT")]
+    WrappedMintMismatch,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn transfer(nft_id: u8, bridge_txid: &str) -> InboundTransfer {
+        InboundTransfer {
+            origin_chain: ChainId::Ethereum,
+            origin_contract_address: "0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed".to_string(),
+            nft_id: [nft_id; 32],
+            receiver: Pubkey::new_from_array([7; 32]),
+            bridge_txid: bridge_txid.to_string(),
+            nonce: 3,
+            name: "Wrapped".to_string(),
+            symbol: "WRP".to_string(),
+            uri: "https://example.com/1.json".to_string(),
+        }
+    }
+
+    fn node(a: [u8; 32], b: [u8; 32]) -> [u8; 32] {
+        if a <= b {
+            keccak::hashv(&[&a, &b]).to_bytes()
+        } else {
+            keccak::hashv(&[&b, &a]).to_bytes()
+        }
+    }
+
+    #[test]
+    fn wrapped_mint_must_match_the_origin_nft() {
+        let address = "0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed";
+        let mint = wrapped_nft_mint(
+            ChainId::Ethereum,
+            address,
+            &[1; 32],
+            &COLLECTION_CREATOR_PROGRAM_ID,
+        );
+
+        assert!(check_wrapped_mint(&mint, ChainId::Ethereum, address, &[1; 32]).is_ok());
+        // Burning the wrapped token of NFT 1 can't release NFT 2
+        assert!(check_wrapped_mint(&mint, ChainId::Ethereum, address, &[2; 32]).is_err());
+        assert!(check_wrapped_mint(&mint, ChainId::Polygon, address, &[1; 32]).is_err());
+        assert!(check_wrapped_mint(
+            &Pubkey::new_from_array([9; 32]),
+            ChainId::Ethereum,
+            address,
+            &[1; 32]
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn merkle_proof_of_every_leaf() {
+        let leaves: Vec<[u8; 32]> = (0..4u32)
+            .map(|i| inbound_leaf_hash(i, &transfer(i as u8, "0xabc")))
+            .collect();
+        let left = node(leaves[0], leaves[1]);
+        let right = node(leaves[2], leaves[3]);
+        let root = node(left, right);
+
+        assert!(verify_merkle_proof(leaves[0], &[leaves[1], right], &root));
+        assert!(verify_merkle_proof(leaves[1], &[leaves[0], right], &root));
+        assert!(verify_merkle_proof(leaves[2], &[leaves[3], left], &root));
+        assert!(verify_merkle_proof(leaves[3], &[leaves[2], left], &root));
+
+        // A single leaf tree is its own root
+        assert!(verify_merkle_proof(leaves[0], &[], &leaves[0]));
+    }
+
+    #[test]
+    fn merkle_proof_rejects_tampering() {
+        let leaves: Vec<[u8; 32]> = (0..4u32)
+            .map(|i| inbound_leaf_hash(i, &transfer(i as u8, "0xabc")))
+            .collect();
+        let left = node(leaves[0], leaves[1]);
+        let right = node(leaves[2], leaves[3]);
+        let root = node(left, right);
+
+        // Wrong sibling, missing level and a leaf claimed at another index
+        assert!(!verify_merkle_proof(leaves[0], &[leaves[2], right], &root));
+        assert!(!verify_merkle_proof(leaves[0], &[leaves[1]], &root));
+        let moved = inbound_leaf_hash(1, &transfer(0, "0xabc"));
+        assert!(!verify_merkle_proof(moved, &[leaves[1], right], &root));
+
+        let mut other = transfer(0, "0xabc");
+        other.receiver = Pubkey::new_from_array([8; 32]);
+        assert!(!verify_merkle_proof(
+            inbound_leaf_hash(0, &other),
+            &[leaves[1], right],
+            &root
+        ));
+    }
+
+    #[test]
+    fn inbound_leaf_commits_to_every_field() {
+        let base = transfer(1, "0xabc");
+        let leaf = inbound_leaf_hash(0, &base);
+        assert_eq!(leaf, inbound_leaf_hash(0, &base.clone()));
+
+        let changes: Vec<fn(&mut InboundTransfer)> = vec![
+            |t| t.origin_chain = ChainId::Polygon,
+            |t| t.origin_contract_address.push('0'),
+            |t| t.nft_id[31] ^= 1,
+            |t| t.receiver = Pubkey::new_from_array([8; 32]),
+            |t| t.bridge_txid.push('0'),
+            |t| t.nonce += 1,
+            |t| t.name.push('0'),
+            |t| t.symbol.push('0'),
+            |t| t.uri.push('0'),
+        ];
+        for change in changes {
+            let mut changed = base.clone();
+            change(&mut changed);
+            assert_ne!(leaf, inbound_leaf_hash(0, &changed));
+        }
+
+        // Hashing the strings keeps the boundary between them
+        let mut shifted = base.clone();
+        shifted.name = "WrappedW".to_string();
+        shifted.symbol = "RP".to_string();
+        assert_ne!(leaf, inbound_leaf_hash(0, &shifted));
+    }
+
+    #[test]
+    fn unlock_message_hash_commits_to_every_field() {
+        let receiver = Pubkey::new_from_array([7; 32]);
+        let mint = Pubkey::new_from_array([9; 32]);
+        let hash = |src_chain, bridge_txid: &str, nft_id, nonce, receiver: &Pubkey| {
+            unlock_message_hash(
+                src_chain,
+                bridge_txid,
+                ChainId::Solana,
+                "collection",
+                nft_id,
+                nonce,
+                receiver,
+                &mint,
+            )
+        };
+        let base = hash(ChainId::Ethereum, "0xabc", [1; 32], 0, &receiver);
+
+        assert_eq!(
+            base,
+            hash(ChainId::Ethereum, "0xabc", [1; 32], 0, &receiver)
+        );
+        assert_ne!(base, hash(ChainId::Polygon, "0xabc", [1; 32], 0, &receiver));
+        assert_ne!(
+            base,
+            hash(ChainId::Ethereum, "0xabd", [1; 32], 0, &receiver)
+        );
+        assert_ne!(
+            base,
+            hash(ChainId::Ethereum, "0xabc", [2; 32], 0, &receiver)
+        );
+        assert_ne!(
+            base,
+            hash(ChainId::Ethereum, "0xabc", [1; 32], 1, &receiver)
+        );
+        assert_ne!(base, hash(ChainId::Ethereum, "0xabc", [1; 32], 0, &mint));
+        assert_ne!(
+            base,
+            unlock_message_hash(
+                ChainId::Ethereum,
+                "0xabc",
+                ChainId::Solana,
+                "collection",
+                [1; 32],
+                0,
+                &receiver,
+                &receiver,
+            )
+        );
+    }
+
+    #[test]
+    fn bridge_in_message_hash_commits_to_the_transfer() {
+        let mint = Pubkey::new_from_array([9; 32]);
+        let base = transfer(1, "0xabc");
+        let message = bridge_in_message_hash(ChainId::Ethereum, &base, &mint);
+
+        assert_eq!(
+            message,
+            bridge_in_message_hash(ChainId::Ethereum, &base.clone(), &mint)
+        );
+        assert_ne!(
+            message,
+            bridge_in_message_hash(ChainId::Polygon, &base, &mint)
+        );
+        assert_ne!(
+            message,
+            bridge_in_message_hash(ChainId::Ethereum, &base, &Pubkey::new_from_array([8; 32]))
+        );
+
+        let mut renamed = base.clone();
+        renamed.uri = "https://example.com/2.json".to_string();
+        assert_ne!(
+            message,
+            bridge_in_message_hash(ChainId::Ethereum, &renamed, &mint)
+        );
+
+        // Signed for bridge_in only, never valid as an unlock
+        assert_ne!(
+            message,
+            unlock_message_hash(
+                ChainId::Ethereum,
+                &base.bridge_txid,
+                base.origin_chain,
+                &base.origin_contract_address,
+                base.nft_id,
+                base.nonce,
+                &base.receiver,
+                &mint,
+            )
+        );
+    }
 }
@@ -1,33 +1,123 @@
 use anchor_lang::prelude::*;
-use anchor_lang::solana_program::pubkey;
-use anchor_lang::solana_program::{hash::hash, system_instruction, sysvar::SysvarId};
+use anchor_lang::solana_program::{
+    hash::hash, pubkey, system_instruction, sysvar, sysvar::SysvarId,
+};
 
+use anchor_lang::system_program::{allocate, assign, create_account, transfer};
+use anchor_lang::system_program::{Allocate, Assign, CreateAccount, Transfer};
 use anchor_spl::{
-    associated_token::AssociatedToken,
+    associated_token::{create, get_associated_token_address, AssociatedToken, Create},
     metadata::{
         create_master_edition_v3, create_metadata_accounts_v3,
         set_and_verify_sized_collection_item, sign_metadata, CreateMasterEditionV3,
         CreateMetadataAccountsV3, Metadata, SetAndVerifySizedCollectionItem, SignMetadata,
     },
-    token::{mint_to, Mint, MintTo, Token, TokenAccount},
+    token::{initialize_mint2, mint_to, InitializeMint2, Mint, MintTo, Token, TokenAccount},
 };
 use mpl_token_metadata::accounts::{MasterEdition, Metadata as MetadataAccount};
+use mpl_token_metadata::instructions::MintV1CpiBuilder;
 use mpl_token_metadata::types::{CollectionDetails, Creator, DataV2};
 
+#[path = "../common/chains.rs"]
+pub mod chains;
+use chains::ChainId;
+#[path = "../common/guardians.rs"]
+pub mod guardians;
+use guardians::{
+    guardian_inbound_message, inbound_transfer_hash, load_guardian_set, verify_guardian_signatures,
+};
+#[path = "../common/origin.rs"]
+pub mod origin;
+use origin::{origin_collection_seed, origin_nft_seed, CollectionInfo, COLLECTION_INFO, NFT_MINT};
+
 declare_id!("AizEzdXgSms3KjkNEsBycmsmJD7LQa2wChwKHaKVXoix");
 
 #[constant]
 pub const COLLECTION: &str = "Collection";
 pub const BRIDGE: &str = "Bridge";
-pub const COLLECTION_INFO: &str = "Collection_Info";
+pub const CREATOR_CONFIG: &str = "Creator_Config";
+pub const ROLE: &str = "Role";
+// Guardian sets are owned by the bridge program
+pub const BRIDGE_PROGRAM_ID: Pubkey = pubkey!("ETWdEcjv3mCb9QzS9Kb6vqv7fi8c3hpLW7Jcsrz2hmEE");
+// Seed of the bridge program PDA that may mint on behalf of the bridge
+pub const BRIDGE_AUTHORITY: &str = "bridge";
 
 #[program]
 pub mod collection_creator {
     use super::*;
 
+    pub fn initialize_config(ctx: Context<InitializeConfig>) -> Result<()> {
+        let creator_config = &mut ctx.accounts.creator_config;
+
+        creator_config.admin = ctx.accounts.signer.key();
+        creator_config.bump = ctx.bumps.creator_config;
+
+        Ok(())
+    }
+
+    pub fn set_admin(ctx: Context<UpdateCreatorConfig>, new_admin: Pubkey) -> Result<()> {
+        let creator_config = &mut ctx.accounts.creator_config;
+        msg!("Admin changed: {} -> {}", creator_config.admin, new_admin);
+        creator_config.admin = new_admin;
+
+        Ok(())
+    }
+
+    pub fn grant_role(ctx: Context<GrantRole>, role: Role, member: Pubkey) -> Result<()> {
+        let role_assignment = &mut ctx.accounts.role_assignment;
+
+        role_assignment.role = role;
+        role_assignment.member = member;
+        role_assignment.bump = ctx.bumps.role_assignment;
+
+        msg!("Role {:?} granted to {}", role, member);
+
+        Ok(())
+    }
+
+    pub fn revoke_role(_ctx: Context<RevokeRole>, role: Role, member: Pubkey) -> Result<()> {
+        msg!("Role {:?} revoked from {}", role, member);
+
+        Ok(())
+    }
+
+    pub fn pause(ctx: Context<PauseCreator>) -> Result<()> {
+        ctx.accounts.creator_config.paused = true;
+        msg!("Collection creator paused by {}", ctx.accounts.signer.key());
+
+        Ok(())
+    }
+
+    pub fn unpause(ctx: Context<PauseCreator>) -> Result<()> {
+        ctx.accounts.creator_config.paused = false;
+        msg!(
+            "Collection creator unpaused by {}",
+            ctx.accounts.signer.key()
+        );
+
+        Ok(())
+    }
+
+    pub fn set_collection_paused(
+        ctx: Context<PauseCollection>,
+        origin_chain: ChainId,
+        origin_contract_address: String,
+        paused: bool,
+    ) -> Result<()> {
+        ctx.accounts.collection_info_account.paused = paused;
+        msg!(
+            "Collection {} on {:?} paused: {}",
+            origin_contract_address,
+            origin_chain,
+            paused
+        );
+
+        Ok(())
+    }
+
     pub fn store_collection_info(
         ctx: Context<StoreCollectionInfo>,
-        origin_chain: String,            // origin_chain passed from client
+        origin_chain: ChainId,           // origin_chain passed from client
         origin_contract_address: String, // origin_contract_address passed from client
     ) -> Result<()> {
         let collection_info_account = &mut ctx.accounts.collection_info_account;
@@ -44,19 +134,20 @@ pub mod collection_creator {
         uri: String,
         name: String,
         symbol: String,
-        origin_chain: String,
+        origin_chain: ChainId,
         origin_contract_address: String,
     ) -> Result<()> {
         // PDA for signing
 
         let collection_info_account = &mut ctx.accounts.collection_info_account;
 
-        if collection_info_account.origin_chain == "SOL" {
+        if collection_info_account.origin_chain == ChainId::Solana {
             msg!("Collection Already present in Solana !!")
         } else {
+            let collection_seed = origin_collection_seed(origin_chain, &origin_contract_address);
             let signer_seeds: &[&[&[u8]]] = &[&[
-                origin_chain.as_bytes().as_ref(),
-                origin_contract_address.as_bytes().as_ref(),
+                COLLECTION.as_bytes(),
+                &collection_seed,
                 &[ctx.bumps.collection_mint],
             ]];
 
@@ -84,12 +175,13 @@ pub mod collection_creator {
         uri: String,
         name: String,
         symbol: String,
-        origin_chain: String,
+        origin_chain: ChainId,
         origin_contract_address: String,
     ) -> Result<()> {
+        let collection_seed = origin_collection_seed(origin_chain, &origin_contract_address);
         let signer_seeds: &[&[&[u8]]] = &[&[
-            origin_chain.as_bytes().as_ref(),
-            origin_contract_address.as_bytes().as_ref(),
+            COLLECTION.as_bytes(),
+            &collection_seed,
             &[ctx.bumps.collection_mint],
         ]];
 
@@ -156,27 +248,100 @@ pub mod collection_creator {
         Ok(())
     }
 
+    #[allow(clippy::too_many_arguments)]
     pub fn create_nft_in_collection(
         ctx: Context<CreateNftInCollection>,
         uri: String,
         name: String,
         symbol: String,
-        origin_chain: String,
+        origin_chain: ChainId,
         origin_contract_address: String,
+        nft_id: [u8; 32],
+        src_chain: ChainId,
+        bridge_txid: String,
+        nonce: u64,
     ) -> Result<()> {
         // let signer_seeds: &[&[&[u8]]] = &[&[SEED.as_bytes(), &[*ctx.bumps.collection_mint]]];
 
+        // Without a minter role the mint must come from the bridge program or the guardians
+        let guardian_attested =
+            ctx.accounts.bridge_authority.is_none() && ctx.accounts.signer_role.is_none();
+        if let Some(bridge_authority) = &ctx.accounts.bridge_authority {
+            verify_bridge_authority(bridge_authority)?;
+        } else if guardian_attested {
+            let guardian_set_account = ctx
+                .accounts
+                .guardian_set
+                .as_ref()
+                .ok_or_else(|| error!(BridgeError::Unauthorized))?;
+            let instructions = ctx
+                .accounts
+                .instructions
+                .as_ref()
+                .ok_or_else(|| error!(BridgeError::InvalidGuardianSignature))?;
+
+            let guardian_set = load_guardian_set(guardian_set_account, &BRIDGE_PROGRAM_ID)?;
+            // The metadata is attested too, a relayer can't mint the NFT under another uri
+            let transfer_hash = inbound_transfer_hash(
+                0,
+                &bridge_txid,
+                origin_chain,
+                &origin_contract_address,
+                &nft_id,
+                nonce,
+                &ctx.accounts.receiver.key(),
+                &name,
+                &symbol,
+                &uri,
+            );
+            let message = guardian_inbound_message(
+                &crate::ID,
+                src_chain,
+                &ctx.accounts.nft_mint.key(),
+                &transfer_hash,
+            );
+            verify_guardian_signatures(instructions, &guardian_set, &message)?;
+        }
+
+        // One mint per origin NFT, a minted NFT comes back through remint_nft_in_collection
+        require!(
+            ctx.accounts.nft_mint.data_is_empty(),
+            BridgeError::NftAlreadyMinted
+        );
+
         let collection_info_account = &mut ctx.accounts.collection_info_account;
 
-        if collection_info_account.origin_chain == "SOL" {
+        if collection_info_account.origin_chain == ChainId::Solana {
             msg!("Collection Already present in Solana !!")
         } else {
+            let collection_seed = origin_collection_seed(origin_chain, &origin_contract_address);
             let signer_seeds: &[&[&[u8]]] = &[&[
-                origin_chain.as_bytes().as_ref(),
-                origin_contract_address.as_bytes().as_ref(),
+                COLLECTION.as_bytes(),
+                &collection_seed,
                 &[ctx.bumps.collection_mint],
             ]];
 
+            let nft_seed = origin_nft_seed(origin_chain, &origin_contract_address, &nft_id);
+            init_nft_mint(
+                &ctx.accounts.signer,
+                &ctx.accounts.nft_mint,
+                &ctx.accounts.collection_mint,
+                &ctx.accounts.system_program,
+                &ctx.accounts.token_program,
+                &[NFT_MINT.as_bytes(), &nft_seed, &[ctx.bumps.nft_mint]],
+            )?;
+            create(CpiContext::new(
+                ctx.accounts.associated_token_program.to_account_info(),
+                Create {
+                    payer: ctx.accounts.signer.to_account_info(),
+                    associated_token: ctx.accounts.token_account.to_account_info(),
+                    authority: ctx.accounts.receiver.to_account_info(),
+                    mint: ctx.accounts.nft_mint.to_account_info(),
+                    system_program: ctx.accounts.system_program.to_account_info(),
+                    token_program: ctx.accounts.token_program.to_account_info(),
+                },
+            ))?;
+
             // mint nft in collection
             mint_to(
                 CpiContext::new_with_signer(
@@ -238,6 +403,19 @@ pub mod collection_creator {
                 ),
                 Some(0),
             )?;
+
+            // Nobody else would verify an NFT the guardians minted
+            if guardian_attested {
+                verify_in_collection(
+                    &ctx.accounts.token_metadata_program,
+                    &ctx.accounts.metadata_account,
+                    &ctx.accounts.collection_mint,
+                    &ctx.accounts.signer,
+                    &ctx.accounts.collection_metadata_account,
+                    &ctx.accounts.collection_master_edition,
+                    signer_seeds,
+                )?;
+            }
         }
 
         Ok(())
@@ -245,47 +423,327 @@ pub mod collection_creator {
 
     pub fn verify_nft_in_collection(
         ctx: Context<VerifyNftInCollection>,
-        origin_chain: String,
+        origin_chain: ChainId,
         origin_contract_address: String,
     ) -> Result<()> {
+        match &ctx.accounts.bridge_authority {
+            Some(bridge_authority) => verify_bridge_authority(bridge_authority)?,
+            None => require!(
+                ctx.accounts.signer_role.is_some(),
+                BridgeError::Unauthorized
+            ),
+        }
+
         let collection_info_account = &mut ctx.accounts.collection_info_account;
 
-        if collection_info_account.origin_chain == "SOL" {
+        if collection_info_account.origin_chain == ChainId::Solana {
             msg!("Collection Already present in Solana !!")
         } else {
+            let collection_seed = origin_collection_seed(origin_chain, &origin_contract_address);
             let signer_seeds: &[&[&[u8]]] = &[&[
-                origin_chain.as_bytes().as_ref(),
-                origin_contract_address.as_bytes().as_ref(),
+                COLLECTION.as_bytes(),
+                &collection_seed,
                 &[ctx.bumps.collection_mint],
             ]];
 
-            // verify nft as part of collection
-            set_and_verify_sized_collection_item(
-                CpiContext::new_with_signer(
-                    ctx.accounts.token_metadata_program.to_account_info(),
-                    SetAndVerifySizedCollectionItem {
-                        metadata: ctx.accounts.metadata_account.to_account_info(),
-                        collection_authority: ctx.accounts.collection_mint.to_account_info(),
-                        payer: ctx.accounts.signer.to_account_info(),
-                        update_authority: ctx.accounts.collection_mint.to_account_info(),
-                        collection_mint: ctx.accounts.collection_mint.to_account_info(),
-                        collection_metadata: ctx
-                            .accounts
-                            .collection_metadata_account
-                            .to_account_info(),
-                        collection_master_edition: ctx
-                            .accounts
-                            .collection_master_edition
-                            .to_account_info(),
+            verify_in_collection(
+                &ctx.accounts.token_metadata_program,
+                &ctx.accounts.metadata_account,
+                &ctx.accounts.collection_mint,
+                &ctx.accounts.signer,
+                &ctx.accounts.collection_metadata_account,
+                &ctx.accounts.collection_master_edition,
+                signer_seeds,
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Mints a wrapped NFT the bridge burned when it left Solana back to `receiver`. The
+    /// burn keeps the mint, metadata and master edition, so the NFT returns at the same
+    /// address.
+    pub fn remint_nft_in_collection(
+        ctx: Context<RemintNftInCollection>,
+        origin_chain: ChainId,
+        origin_contract_address: String,
+    ) -> Result<()> {
+        verify_bridge_authority(&ctx.accounts.bridge_authority)?;
+        require!(
+            ctx.accounts.collection_info_account.origin_chain != ChainId::Solana,
+            BridgeError::CollectionNotAllowed
+        );
+
+        let collection_seed = origin_collection_seed(origin_chain, &origin_contract_address);
+        let signer_seeds: &[&[&[u8]]] = &[&[
+            COLLECTION.as_bytes(),
+            &collection_seed,
+            &[ctx.bumps.collection_mint],
+        ]];
+
+        // Token Metadata mints a master edition again once its supply is back to zero
+        let token_metadata_program = ctx.accounts.token_metadata_program.to_account_info();
+        let token_account = ctx.accounts.token_account.to_account_info();
+        let receiver = ctx.accounts.receiver.to_account_info();
+        let metadata = ctx.accounts.metadata_account.to_account_info();
+        let master_edition = ctx.accounts.master_edition.to_account_info();
+        let nft_mint = ctx.accounts.nft_mint.to_account_info();
+        let collection_mint = ctx.accounts.collection_mint.to_account_info();
+        let payer = ctx.accounts.signer.to_account_info();
+        let system_program = ctx.accounts.system_program.to_account_info();
+        let instructions = ctx.accounts.instructions.to_account_info();
+        let token_program = ctx.accounts.token_program.to_account_info();
+        let associated_token_program = ctx.accounts.associated_token_program.to_account_info();
+
+        MintV1CpiBuilder::new(&token_metadata_program)
+            .token(&token_account)
+            .token_owner(Some(&receiver))
+            .metadata(&metadata)
+            .master_edition(Some(&master_edition))
+            .mint(&nft_mint)
+            .authority(&collection_mint)
+            .payer(&payer)
+            .system_program(&system_program)
+            .sysvar_instructions(&instructions)
+            .spl_token_program(&token_program)
+            .spl_ata_program(&associated_token_program)
+            .amount(1)
+            .invoke_signed(signer_seeds)?;
+
+        Ok(())
+    }
+}
+
+/// Verifies the NFT of `metadata_account` as part of `collection_mint`, signing as the
+/// collection mint PDA
+fn verify_in_collection<'info>(
+    token_metadata_program: &Program<'info, Metadata>,
+    metadata_account: &UncheckedAccount<'info>,
+    collection_mint: &Account<'info, Mint>,
+    payer: &Signer<'info>,
+    collection_metadata_account: &UncheckedAccount<'info>,
+    collection_master_edition: &UncheckedAccount<'info>,
+    signer_seeds: &[&[&[u8]]],
+) -> Result<()> {
+    // verify nft as part of collection
+    set_and_verify_sized_collection_item(
+        CpiContext::new_with_signer(
+            token_metadata_program.to_account_info(),
+            SetAndVerifySizedCollectionItem {
+                metadata: metadata_account.to_account_info(),
+                collection_authority: collection_mint.to_account_info(),
+                payer: payer.to_account_info(),
+                update_authority: collection_mint.to_account_info(),
+                collection_mint: collection_mint.to_account_info(),
+                collection_metadata: collection_metadata_account.to_account_info(),
+                collection_master_edition: collection_master_edition.to_account_info(),
+            },
+            signer_seeds,
+        ),
+        None,
+    )
+}
+
+/// Creates and initializes the mint of a wrapped NFT at its PDA, with the collection mint
+/// as mint and freeze authority. A PDA funded up front is allocated in place.
+fn init_nft_mint<'info>(
+    payer: &Signer<'info>,
+    nft_mint: &UncheckedAccount<'info>,
+    collection_mint: &Account<'info, Mint>,
+    system_program: &Program<'info, System>,
+    token_program: &Program<'info, Token>,
+    mint_seeds: &[&[u8]],
+) -> Result<()> {
+    let signer_seeds = &[mint_seeds];
+    let rent = Rent::get()?.minimum_balance(Mint::LEN);
+    let lamports = nft_mint.lamports();
+
+    if lamports == 0 {
+        create_account(
+            CpiContext::new_with_signer(
+                system_program.to_account_info(),
+                CreateAccount {
+                    from: payer.to_account_info(),
+                    to: nft_mint.to_account_info(),
+                },
+                signer_seeds,
+            ),
+            rent,
+            Mint::LEN as u64,
+            &Token::id(),
+        )?;
+    } else {
+        if lamports < rent {
+            transfer(
+                CpiContext::new(
+                    system_program.to_account_info(),
+                    Transfer {
+                        from: payer.to_account_info(),
+                        to: nft_mint.to_account_info(),
                     },
-                    &signer_seeds,
                 ),
-                None,
+                rent - lamports,
             )?;
         }
-        Ok(())
+        allocate(
+            CpiContext::new_with_signer(
+                system_program.to_account_info(),
+                Allocate {
+                    account_to_allocate: nft_mint.to_account_info(),
+                },
+                signer_seeds,
+            ),
+            Mint::LEN as u64,
+        )?;
+        assign(
+            CpiContext::new_with_signer(
+                system_program.to_account_info(),
+                Assign {
+                    account_to_assign: nft_mint.to_account_info(),
+                },
+                signer_seeds,
+            ),
+            &Token::id(),
+        )?;
     }
 
+    initialize_mint2(
+        CpiContext::new(
+            token_program.to_account_info(),
+            InitializeMint2 {
+                mint: nft_mint.to_account_info(),
+            },
+        ),
+        0,
+        &collection_mint.key(),
+        Some(&collection_mint.key()),
+    )
+}
+
+fn verify_bridge_authority(bridge_authority: &Signer) -> Result<()> {
+    let (address, _) =
+        Pubkey::find_program_address(&[BRIDGE_AUTHORITY.as_bytes()], &BRIDGE_PROGRAM_ID);
+    require_keys_eq!(bridge_authority.key(), address, BridgeError::Unauthorized);
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct InitializeConfig<'info> {
+    #[account(
+        init,
+        payer = signer,
+        seeds=[CREATOR_CONFIG.as_bytes()],
+        bump,
+        space = 8 + CreatorConfig::INIT_SPACE
+    )]
+    pub creator_config: Account<'info, CreatorConfig>,
+
+    // Only the upgrade authority of this program may create the config
+    #[account(constraint = program.programdata_address()? == Some(program_data.key()))]
+    pub program: Program<'info, crate::program::CollectionCreator>,
+
+    #[account(constraint = program_data.upgrade_authority_address == Some(signer.key()) @ BridgeError::Unauthorized)]
+    pub program_data: Account<'info, ProgramData>,
+
+    #[account(mut)]
+    pub signer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateCreatorConfig<'info> {
+    #[account(
+        mut,
+        seeds=[CREATOR_CONFIG.as_bytes()],
+        bump = creator_config.bump,
+        has_one = admin @ BridgeError::Unauthorized
+    )]
+    pub creator_config: Account<'info, CreatorConfig>,
+
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct PauseCreator<'info> {
+    #[account(
+        mut,
+        seeds=[CREATOR_CONFIG.as_bytes()],
+        bump = creator_config.bump
+    )]
+    pub creator_config: Account<'info, CreatorConfig>,
+
+    #[account(
+        seeds=[ROLE.as_bytes(), &[Role::Pauser as u8], signer.key().as_ref()],
+        bump = signer_role.bump
+    )]
+    pub signer_role: Account<'info, RoleAssignment>,
+
+    pub signer: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(origin_chain: ChainId, origin_contract_address: String)]
+pub struct PauseCollection<'info> {
+    #[account(
+        mut,
+        seeds=[COLLECTION_INFO.as_bytes(), &origin_collection_seed(origin_chain, &origin_contract_address)],
+        bump
+    )]
+    pub collection_info_account: Account<'info, CollectionInfo>,
+
+    #[account(
+        seeds=[ROLE.as_bytes(), &[Role::Pauser as u8], signer.key().as_ref()],
+        bump = signer_role.bump
+    )]
+    pub signer_role: Account<'info, RoleAssignment>,
+
+    pub signer: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(role: Role, member: Pubkey)]
+pub struct GrantRole<'info> {
+    #[account(
+        seeds=[CREATOR_CONFIG.as_bytes()],
+        bump = creator_config.bump,
+        has_one = admin @ BridgeError::Unauthorized
+    )]
+    pub creator_config: Account<'info, CreatorConfig>,
+
+    #[account(
+        init,
+        payer = admin,
+        seeds=[ROLE.as_bytes(), &[role as u8], member.as_ref()],
+        bump,
+        space = 8 + RoleAssignment::INIT_SPACE
+    )]
+    pub role_assignment: Account<'info, RoleAssignment>,
+
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(role: Role, member: Pubkey)]
+pub struct RevokeRole<'info> {
+    #[account(
+        seeds=[CREATOR_CONFIG.as_bytes()],
+        bump = creator_config.bump,
+        has_one = admin @ BridgeError::Unauthorized
+    )]
+    pub creator_config: Account<'info, CreatorConfig>,
+
+    #[account(
+        mut,
+        close = admin,
+        seeds=[ROLE.as_bytes(), &[role as u8], member.as_ref()],
+        bump = role_assignment.bump
+    )]
+    pub role_assignment: Account<'info, RoleAssignment>,
+
+    #[account(mut)]
+    pub admin: Signer<'info>,
 }
 
 #[derive(Accounts)]
@@ -293,10 +751,16 @@ pub mod collection_creator {
         uri: String,
         name: String,
         symbol: String,
-        origin_chain: String,
+        origin_chain: ChainId,
         origin_contract_address:String)]
 pub struct CreateCollectionNft<'info> {
-    #[account(mut,address=pubkey!("7QHySLfCkeSBUGxtqM3WdeKv9X4YUXMeTn3gr5fRPCGU"))]
+    #[account(
+        seeds=[ROLE.as_bytes(), &[Role::Manager as u8], authority.key().as_ref()],
+        bump = authority_role.bump
+    )]
+    pub authority_role: Account<'info, RoleAssignment>,
+
+    #[account(mut)]
     pub authority: Signer<'info>,
 
     #[account(
@@ -314,10 +778,8 @@ pub struct CreateCollectionNft<'info> {
     mint::decimals = 0,
     mint::authority = collection_mint,
     mint::freeze_authority = collection_mint,
-    seeds = [origin_chain.as_bytes() , origin_contract_address.as_bytes()],
+    seeds = [COLLECTION.as_bytes(), &origin_collection_seed(origin_chain, &origin_contract_address)],
     bump,
-    // seeds = [COLLECTION.as_bytes() , origin_chain.as_bytes() , origin_contract_address.as_bytes()],
-    // seeds = [hash([COLLECTION , &origin_chain , &origin_contract_address].concat().as_bytes()).as_ref()],
     )]
     pub collection_mint: Account<'info, Mint>,
 
@@ -345,8 +807,8 @@ pub struct CreateCollectionNft<'info> {
 
     #[account(
     mut , 
-    // seeds=[COLLECTION_INFO.as_bytes(), origin_chain.as_bytes() , origin_contract_address.as_bytes()],
-    // bump
+    seeds=[COLLECTION_INFO.as_bytes(), &origin_collection_seed(origin_chain, &origin_contract_address)],
+    bump
     )]
     pub collection_info_account: Account<'info, CollectionInfo>,
     pub system_program: Program<'info, System>,
@@ -361,15 +823,43 @@ pub struct CreateCollectionNft<'info> {
         uri: String,
         name: String,
         symbol: String,
-        origin_chain: String ,
-        origin_contract_address:String)]
+        origin_chain: ChainId ,
+        origin_contract_address:String,
+        nft_id: [u8; 32],
+        src_chain: ChainId,
+        bridge_txid: String,
+        nonce: u64)]
 pub struct CreateNftInCollection<'info> {
-    #[account(mut,address=pubkey!("7QHySLfCkeSBUGxtqM3WdeKv9X4YUXMeTn3gr5fRPCGU"))]
+    #[account(
+        seeds=[CREATOR_CONFIG.as_bytes()],
+        bump = creator_config.bump,
+        constraint = !creator_config.paused @ BridgeError::BridgePaused
+    )]
+    pub creator_config: Account<'info, CreatorConfig>,
+
+    // Either a minter, or a guardian attestation through the accounts below
+    #[account(
+        seeds=[ROLE.as_bytes(), &[Role::Minter as u8], signer.key().as_ref()],
+        bump = signer_role.bump
+    )]
+    pub signer_role: Option<Account<'info, RoleAssignment>>,
+
+    /// CHECK: bridge owned guardian set, validated in load_guardian_set
+    pub guardian_set: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: instructions sysvar, read for the guardian Ed25519 instruction
+    #[account(address = sysvar::instructions::ID)]
+    pub instructions: Option<UncheckedAccount<'info>>,
+
+    // Set when the bridge program mints through CPI
+    pub bridge_authority: Option<Signer<'info>>,
+
+    #[account(mut)]
     pub signer: Signer<'info>,
 
     #[account(
     mut,
-    seeds = [origin_chain.as_bytes() , origin_contract_address.as_bytes()],
+    seeds = [COLLECTION.as_bytes(), &origin_collection_seed(origin_chain, &origin_contract_address)],
     bump,
     )]
     pub collection_mint: Account<'info, Mint>,
@@ -388,19 +878,18 @@ pub struct CreateNftInCollection<'info> {
     )]
     pub collection_master_edition: UncheckedAccount<'info>,
 
+    /// CHECK: created in the instruction, an existing mint is rejected as already minted
     #[account(
-    init_if_needed,
-    payer = signer,
-    mint::decimals = 0,
-    mint::authority = collection_mint,
-    mint::freeze_authority = collection_mint
+    mut,
+    seeds = [NFT_MINT.as_bytes(), &origin_nft_seed(origin_chain, &origin_contract_address, &nft_id)],
+    bump,
     )]
-    pub nft_mint: Account<'info, Mint>,
+    pub nft_mint: UncheckedAccount<'info>,
 
-    /// CHECK:
+    /// CHECK: metaplex metadata of nft_mint, created in the instruction
     #[account(
     mut,
-    // address=MetadataAccount::find_pda(&nft_mint.key()).0
+    address = MetadataAccount::find_pda(&nft_mint.key()).0
     )]
     pub metadata_account: UncheckedAccount<'info>,
 
@@ -411,21 +900,21 @@ pub struct CreateNftInCollection<'info> {
     )]
     pub master_edition: UncheckedAccount<'info>,
 
+    /// CHECK: associated token account of receiver, created after nft_mint
     #[account(
-    init_if_needed,
-    payer = signer,
-    associated_token::mint = nft_mint,
-    associated_token::authority = receiver
+    mut,
+    address = get_associated_token_address(&receiver.key(), &nft_mint.key())
     )]
-    pub token_account: Account<'info, TokenAccount>,
+    pub token_account: UncheckedAccount<'info>,
 
     #[account(mut)]
     pub receiver: AccountInfo<'info>,
 
     #[account(
     mut , 
-    seeds=[COLLECTION_INFO.as_bytes(), origin_chain.as_bytes() , origin_contract_address.as_bytes()],
-    bump
+    seeds=[COLLECTION_INFO.as_bytes(), &origin_collection_seed(origin_chain, &origin_contract_address)],
+    bump,
+    constraint = !collection_info_account.paused @ BridgeError::BridgePaused
     )]
     pub collection_info_account: Account<'info, CollectionInfo>,
 
@@ -437,14 +926,30 @@ pub struct CreateNftInCollection<'info> {
 }
 
 #[derive(Accounts)]
-#[instruction(origin_chain: String , origin_contract_address:String)]
+#[instruction(origin_chain: ChainId , origin_contract_address:String)]
 pub struct VerifyNftInCollection<'info> {
-    #[account(mut,address=pubkey!("7QHySLfCkeSBUGxtqM3WdeKv9X4YUXMeTn3gr5fRPCGU"))]
+    #[account(
+        seeds=[CREATOR_CONFIG.as_bytes()],
+        bump = creator_config.bump,
+        constraint = !creator_config.paused @ BridgeError::BridgePaused
+    )]
+    pub creator_config: Account<'info, CreatorConfig>,
+
+    #[account(
+        seeds=[ROLE.as_bytes(), &[Role::Minter as u8], signer.key().as_ref()],
+        bump = signer_role.bump
+    )]
+    pub signer_role: Option<Account<'info, RoleAssignment>>,
+
+    // Set when the bridge program verifies through CPI
+    pub bridge_authority: Option<Signer<'info>>,
+
+    #[account(mut)]
     pub signer: Signer<'info>,
 
     #[account(
     mut,
-    seeds = [origin_chain.as_bytes() , origin_contract_address.as_bytes()],
+    seeds = [COLLECTION.as_bytes(), &origin_collection_seed(origin_chain, &origin_contract_address)],
     bump,
     )]
     pub collection_mint: Account<'info, Mint>,
@@ -497,8 +1002,9 @@ pub struct VerifyNftInCollection<'info> {
 
     #[account(
     mut , 
-    seeds=[COLLECTION_INFO.as_bytes(), origin_chain.as_bytes() , origin_contract_address.as_bytes()],
-    bump
+    seeds=[COLLECTION_INFO.as_bytes(), &origin_collection_seed(origin_chain, &origin_contract_address)],
+    bump,
+    constraint = !collection_info_account.paused @ BridgeError::BridgePaused
     )]
     pub collection_info_account: Account<'info, CollectionInfo>,
 
@@ -510,24 +1016,141 @@ pub struct VerifyNftInCollection<'info> {
 }
 
 #[derive(Accounts)]
-#[instruction(origin_chain: String , origin_contract_address:String)]
+#[instruction(origin_chain: ChainId, origin_contract_address: String)]
+pub struct RemintNftInCollection<'info> {
+    #[account(
+        seeds=[CREATOR_CONFIG.as_bytes()],
+        bump = creator_config.bump,
+        constraint = !creator_config.paused @ BridgeError::BridgePaused
+    )]
+    pub creator_config: Account<'info, CreatorConfig>,
+
+    // Only the bridge program burns wrapped NFTs, so only it mints them again
+    pub bridge_authority: Signer<'info>,
+
+    #[account(mut)]
+    pub signer: Signer<'info>,
+
+    #[account(
+        seeds = [COLLECTION.as_bytes(), &origin_collection_seed(origin_chain, &origin_contract_address)],
+        bump,
+    )]
+    pub collection_mint: Account<'info, Mint>,
+
+    // Token Metadata checks the mint is a master edition of collection_mint
+    #[account(mut)]
+    pub nft_mint: Account<'info, Mint>,
+
+    /// CHECK: checked by Token Metadata
+    pub metadata_account: UncheckedAccount<'info>,
+
+    /// CHECK: checked by Token Metadata
+    #[account(mut)]
+    pub master_edition: UncheckedAccount<'info>,
+
+    /// CHECK: receiver's associated token account, created by Token Metadata if needed
+    #[account(mut)]
+    pub token_account: UncheckedAccount<'info>,
+
+    pub receiver: AccountInfo<'info>,
+
+    #[account(
+        seeds=[COLLECTION_INFO.as_bytes(), &origin_collection_seed(origin_chain, &origin_contract_address)],
+        bump,
+        constraint = !collection_info_account.paused @ BridgeError::BridgePaused
+    )]
+    pub collection_info_account: Account<'info, CollectionInfo>,
+
+    /// CHECK: instructions sysvar, required by Token Metadata
+    #[account(address = sysvar::instructions::ID)]
+    pub instructions: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub token_metadata_program: Program<'info, Metadata>,
+}
+
+#[derive(Accounts)]
+#[instruction(origin_chain: ChainId , origin_contract_address:String)]
 pub struct StoreCollectionInfo<'info> {
     #[account(
         init_if_needed,
-        seeds = [COLLECTION_INFO.as_bytes(), origin_chain.as_bytes(), origin_contract_address.as_bytes()],
+        seeds=[COLLECTION_INFO.as_bytes(), &origin_collection_seed(origin_chain, &origin_contract_address)],
         bump,
         payer = user,
-        space = 8 + std::mem::size_of::<CollectionInfo>(),
+        space = 8 + CollectionInfo::INIT_SPACE,
     )]
     pub collection_info_account: Box<Account<'info, CollectionInfo>>,
 
+    #[account(
+        seeds=[ROLE.as_bytes(), &[Role::Manager as u8], user.key().as_ref()],
+        bump = user_role.bump
+    )]
+    pub user_role: Account<'info, RoleAssignment>,
+
     #[account(mut)]
     pub user: Signer<'info>,
     pub system_program: Program<'info, System>,
 }
 
 #[account]
-pub struct CollectionInfo {
-    pub origin_chain: String,
-    pub origin_contract_address: String,
+#[derive(InitSpace)]
+pub struct CreatorConfig {
+    pub admin: Pubkey,
+    pub paused: bool,
+    pub bump: u8,
+}
+
+/// Roles mirroring the EVM BridgeManager. The admin role is held by
+/// `CreatorConfig.admin` and moved with `set_admin`.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq, InitSpace)]
+pub enum Role {
+    Manager,
+    Minter,
+    Pauser,
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct RoleAssignment {
+    pub role: Role,
+    pub member: Pubkey,
+    pub bump: u8,
+}
+
+/// Same codes as the `BridgeError` of the bridge program (`SOL/bridge`)
+#[error_code]
+pub enum BridgeError {
+    #[msg("Chain is not registered or has been disabled")]
+    ChainNotAllowed,
+    #[msg("Collection is not mapped to the destination chain")]
+    CollectionNotAllowed,
+    #[msg("Attestation is not signed by the bridge operator")]
+    SenderNotBridgeManager,
+    #[msg("Fee does not match the configured bridge fee")]
+    InvalidFee,
+    // Fees are never refunded on Solana, kept so the codes line up with EVM
+    #[msg("Fee refund failed")]
+    RefundFailed,
+    #[msg("Sender does not hold the NFT")]
+    InvalidNftOwner,
+    #[msg("Number of NFTs is zero or above the batch limit")]
+    InvalidNftLength,
+    #[msg("Transfer with this bridge txid has already been processed")]
+    TransferAlreadyProcessed,
+    #[msg("Bridge is paused")]
+    BridgePaused,
+    #[msg("Destination address is not valid for the destination chain")]
+    InvalidDestinationAddress,
+    #[msg("Signer is not authorized to perform this action")]
+    Unauthorized,
+    #[msg("Guardian set is invalid")]
+    InvalidGuardianSet,
+    #[msg("Guardian set has expired")]
+    GuardianSetExpired,
+    #[msg("Guardian signatures are missing or below the threshold")]
+    InvalidGuardianSignature,
+    #[msg("Wrapped NFT has already been minted")]
+    NftAlreadyMinted,
 }
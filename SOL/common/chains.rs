@@ -0,0 +1,56 @@
+// Chain identifiers shared by the bridge and collection creator programs.
+use anchor_lang::prelude::*;
+
+/// Chains the bridge can talk to.
+///
+/// The discriminant (`chain as u8`) is what goes into PDA seeds and signed messages, so
+/// variants must only ever be appended.
+///
+/// | Variant     | Stacks chain string | CCIP selector (mainnet) | CCIP selector (testnet)          |
+/// |-------------|---------------------|-------------------------|----------------------------------|
+/// | `Solana`    | `"SOL"`             | `124615329519749607`    | `16423721717087811551` (devnet)  |
+/// | `Ethereum`  | `"ETH"`             | `5009297550715157269`   | `16015286601757825753` (Sepolia) |
+/// | `Stacks`    | `"STX"`             | -                       | -                                |
+/// | `Flow`      | `"FLOW"`            | -                       | -                                |
+/// | `Polygon`   | `"POLYGON"`         | `4051577828743386545`   | `16281711391670634445` (Amoy)    |
+/// | `Arbitrum`  | `"ARBITRUM"`        | `4949039107694359620`   | `3478487238524512106` (Sepolia)  |
+/// | `Base`      | `"BASE"`            | `15971525489660198786`  | `10344971235874465080` (Sepolia) |
+/// | `Avalanche` | `"AVALANCHE"`       | `6433500567565415381`   | `14767482510784806043` (Fuji)    |
+/// | `Bsc`       | `"BSC"`             | `11344663589394136015`  | `13264668187771770619` (testnet) |
+/// | `Optimism`  | `"OPTIMISM"`        | `3734403246176062136`   | `5224473277236331295` (Sepolia)  |
+///
+/// The EVM BridgeManager keys chains by the `uint64` CCIP chain selector, its
+/// `CURRENT_CHAIN_ID` is the selector of the chain it is deployed on. The selector of each
+/// chain on the current network is registered in the bridge's `ChainConfig.ccip_chain_selector`
+/// (zero for non CCIP chains).
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq, InitSpace)]
+pub enum ChainId {
+    Solana,
+    Ethereum,
+    Stacks,
+    Flow,
+    Polygon,
+    Arbitrum,
+    Base,
+    Avalanche,
+    Bsc,
+    Optimism,
+}
+
+impl ChainId {
+    /// Name of the chain in the Stacks bridge `(string-ascii 10)` chain maps
+    pub fn stacks_name(self) -> &'static str {
+        match self {
+            ChainId::Solana => "SOL",
+            ChainId::Ethereum => "ETH",
+            ChainId::Stacks => "STX",
+            ChainId::Flow => "FLOW",
+            ChainId::Polygon => "POLYGON",
+            ChainId::Arbitrum => "ARBITRUM",
+            ChainId::Base => "BASE",
+            ChainId::Avalanche => "AVALANCHE",
+            ChainId::Bsc => "BSC",
+            ChainId::Optimism => "OPTIMISM",
+        }
+    }
+}
@@ -0,0 +1,256 @@
+// Guardian attestations shared by the bridge and collection creator programs.
+// Guardian sets are stored by the bridge program, the collection creator only reads them.
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::hash::{hash, hashv};
+use anchor_lang::solana_program::keccak;
+use anchor_lang::solana_program::{ed25519_program, sysvar::instructions};
+
+use crate::chains::ChainId;
+use crate::BridgeError;
+
+pub const GUARDIAN_SET: &str = "guardian_set";
+pub const GUARDIAN_DOMAIN: &str = "nft_bridge_guardian_v1";
+pub const GUARDIAN_BATCH_DOMAIN: &str = "nft_bridge_guardian_batch_v1";
+pub const GUARDIAN_INBOUND_DOMAIN: &str = "nft_bridge_guardian_inbound_v1";
+pub const MAX_GUARDIANS: usize = 19;
+
+// Layout of the Ed25519 program instruction data
+const SIGNATURE_OFFSETS_START: usize = 2;
+const SIGNATURE_OFFSETS_SIZE: usize = 14;
+const CURRENT_INSTRUCTION: u16 = u16::MAX;
+
+#[account]
+#[derive(InitSpace)]
+pub struct GuardianSet {
+    pub index: u32,
+    #[max_len(MAX_GUARDIANS)]
+    pub keys: Vec<Pubkey>,
+    pub threshold: u8,
+    // Zero while the set is only proposed
+    pub activated_at: i64,
+    // Zero while the set is current, otherwise the time it stops being accepted
+    pub expiration_time: i64,
+    pub bump: u8,
+}
+
+impl GuardianSet {
+    pub fn validate(keys: &[Pubkey], threshold: u8) -> Result<()> {
+        require!(
+            !keys.is_empty() && keys.len() <= MAX_GUARDIANS,
+            BridgeError::InvalidGuardianSet
+        );
+        require!(
+            threshold > 0 && threshold as usize <= keys.len(),
+            BridgeError::InvalidGuardianSet
+        );
+
+        for (i, key) in keys.iter().enumerate() {
+            require!(!keys[..i].contains(key), BridgeError::InvalidGuardianSet);
+        }
+
+        Ok(())
+    }
+
+    pub fn is_active(&self, now: i64) -> bool {
+        self.activated_at != 0 && (self.expiration_time == 0 || now < self.expiration_time)
+    }
+}
+
+/// Loads a guardian set owned by `bridge_program`, checking it sits at its PDA
+pub fn load_guardian_set(account: &AccountInfo, bridge_program: &Pubkey) -> Result<GuardianSet> {
+    require_keys_eq!(
+        *account.owner,
+        *bridge_program,
+        BridgeError::InvalidGuardianSet
+    );
+
+    let guardian_set = GuardianSet::try_deserialize(&mut &account.try_borrow_data()?[..])?;
+    let address = Pubkey::create_program_address(
+        &[
+            GUARDIAN_SET.as_bytes(),
+            &guardian_set.index.to_le_bytes(),
+            &[guardian_set.bump],
+        ],
+        bridge_program,
+    )
+    .map_err(|_| error!(BridgeError::InvalidGuardianSet))?;
+    require_keys_eq!(address, account.key(), BridgeError::InvalidGuardianSet);
+
+    Ok(guardian_set)
+}
+
+/// Canonical 32 byte message guardians sign for an inbound transfer
+pub fn guardian_message(
+    program_id: &Pubkey,
+    src_chain: ChainId,
+    mint: &Pubkey,
+    recipient: &Pubkey,
+    bridge_txid: &str,
+    nft_id: &[u8; 32],
+    nonce: u64,
+) -> [u8; 32] {
+    hashv(&[
+        GUARDIAN_DOMAIN.as_bytes(),
+        program_id.as_ref(),
+        &[src_chain as u8],
+        mint.as_ref(),
+        recipient.as_ref(),
+        &hash(bridge_txid.as_bytes()).to_bytes(),
+        nft_id,
+        &nonce.to_be_bytes(),
+    ])
+    .to_bytes()
+}
+
+/// Message guardians sign for an inbound transfer of several NFTs to one recipient
+pub fn guardian_batch_message(
+    program_id: &Pubkey,
+    src_chain: ChainId,
+    mints: &[Pubkey],
+    recipient: &Pubkey,
+    bridge_txid: &str,
+    nft_ids: &[[u8; 32]],
+    nonces: &[u64],
+) -> [u8; 32] {
+    let mints: Vec<&[u8]> = mints.iter().map(|mint| mint.as_ref()).collect();
+    let nonces: Vec<u8> = nonces
+        .iter()
+        .flat_map(|nonce| nonce.to_be_bytes())
+        .collect();
+
+    hashv(&[
+        GUARDIAN_BATCH_DOMAIN.as_bytes(),
+        program_id.as_ref(),
+        &[src_chain as u8],
+        &hashv(&mints).to_bytes(),
+        recipient.as_ref(),
+        &hash(bridge_txid.as_bytes()).to_bytes(),
+        &hash(&nft_ids.concat()).to_bytes(),
+        &hash(&nonces).to_bytes(),
+    ])
+    .to_bytes()
+}
+
+/// Message guardians sign for a transfer that carries its own metadata, `transfer_hash`
+/// commits to every field of it so none can be swapped
+pub fn guardian_inbound_message(
+    program_id: &Pubkey,
+    src_chain: ChainId,
+    mint: &Pubkey,
+    transfer_hash: &[u8; 32],
+) -> [u8; 32] {
+    hashv(&[
+        GUARDIAN_INBOUND_DOMAIN.as_bytes(),
+        program_id.as_ref(),
+        &[src_chain as u8],
+        transfer_hash,
+        mint.as_ref(),
+    ])
+    .to_bytes()
+}
+
+/// Hash of an inbound transfer with the metadata it is minted with, the leaf layout of the
+/// bridge's inbound batches. Strings are hashed so the encoding stays unambiguous.
+#[allow(clippy::too_many_arguments)]
+pub fn inbound_transfer_hash(
+    index: u32,
+    bridge_txid: &str,
+    origin_chain: ChainId,
+    origin_contract_address: &str,
+    nft_id: &[u8; 32],
+    nonce: u64,
+    receiver: &Pubkey,
+    name: &str,
+    symbol: &str,
+    uri: &str,
+) -> [u8; 32] {
+    keccak::hashv(&[
+        &index.to_be_bytes(),
+        &keccak::hash(bridge_txid.as_bytes()).to_bytes(),
+        &[origin_chain as u8],
+        &keccak::hash(origin_contract_address.as_bytes()).to_bytes(),
+        nft_id,
+        &nonce.to_be_bytes(),
+        receiver.as_ref(),
+        &keccak::hash(name.as_bytes()).to_bytes(),
+        &keccak::hash(symbol.as_bytes()).to_bytes(),
+        &keccak::hash(uri.as_bytes()).to_bytes(),
+    ])
+    .to_bytes()
+}
+
+/// Checks that the instruction right before this one is an Ed25519 program
+/// instruction carrying `message` signed by at least `threshold` distinct guardians.
+pub fn verify_guardian_signatures(
+    instructions_sysvar: &AccountInfo,
+    guardian_set: &GuardianSet,
+    message: &[u8; 32],
+) -> Result<()> {
+    require!(
+        guardian_set.is_active(Clock::get()?.unix_timestamp),
+        BridgeError::GuardianSetExpired
+    );
+
+    let current_index = instructions::load_current_index_checked(instructions_sysvar)?;
+    require!(current_index > 0, BridgeError::InvalidGuardianSignature);
+    let ed25519_ix = instructions::load_instruction_at_checked(
+        (current_index - 1) as usize,
+        instructions_sysvar,
+    )?;
+    require_keys_eq!(
+        ed25519_ix.program_id,
+        ed25519_program::ID,
+        BridgeError::InvalidGuardianSignature
+    );
+
+    let data = &ed25519_ix.data;
+    require!(
+        data.len() >= SIGNATURE_OFFSETS_START,
+        BridgeError::InvalidGuardianSignature
+    );
+
+    let mut signed_by = [false; MAX_GUARDIANS];
+    for i in 0..data[0] as usize {
+        let start = SIGNATURE_OFFSETS_START + i * SIGNATURE_OFFSETS_SIZE;
+        let offsets = data
+            .get(start..start + SIGNATURE_OFFSETS_SIZE)
+            .ok_or_else(|| error!(BridgeError::InvalidGuardianSignature))?;
+        let read = |at: usize| u16::from_le_bytes([offsets[at], offsets[at + 1]]);
+
+        // Signature, key and message must all live in the Ed25519 instruction itself
+        require!(
+            read(2) == CURRENT_INSTRUCTION
+                && read(6) == CURRENT_INSTRUCTION
+                && read(12) == CURRENT_INSTRUCTION,
+            BridgeError::InvalidGuardianSignature
+        );
+
+        let public_key_offset = read(4) as usize;
+        let message_offset = read(8) as usize;
+        let message_size = read(10) as usize;
+        require!(
+            message_size == message.len()
+                && data.get(message_offset..message_offset + message_size) == Some(&message[..]),
+            BridgeError::InvalidGuardianSignature
+        );
+
+        let public_key = data
+            .get(public_key_offset..public_key_offset + 32)
+            .ok_or_else(|| error!(BridgeError::InvalidGuardianSignature))?;
+        if let Some(guardian) = guardian_set
+            .keys
+            .iter()
+            .position(|key| key.as_ref() == public_key)
+        {
+            signed_by[guardian] = true;
+        }
+    }
+
+    let signatures = signed_by.iter().filter(|signed| **signed).count();
+    require!(
+        signatures >= guardian_set.threshold as usize,
+        BridgeError::InvalidGuardianSignature
+    );
+
+    Ok(())
+}
@@ -0,0 +1,91 @@
+// Seeds of PDAs keyed by origin chain identifiers, shared by the bridge and collection creator programs.
+// Origin contract addresses (a 42 char EVM address, a Stacks contract principal) and token ids do not
+// fit the 32 byte seed limit, so the canonical length prefixed encoding is hashed into a single seed.
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::hash::hashv;
+
+use crate::chains::ChainId;
+use crate::BridgeError;
+
+pub const COLLECTION_INFO: &str = "Collection_Info";
+pub const NFT_MINT: &str = "Nft_Mint";
+// A Stacks contract principal: 41 char address, `.` and a contract name of up to 128 chars
+pub const MAX_ORIGIN_CONTRACT_ADDRESS_LEN: usize = 170;
+
+/// Origin of a wrapped collection, kept by the collection creator program
+#[account]
+#[derive(InitSpace)]
+pub struct CollectionInfo {
+    pub origin_chain: ChainId,
+    #[max_len(MAX_ORIGIN_CONTRACT_ADDRESS_LEN)]
+    pub origin_contract_address: String,
+    pub paused: bool,
+}
+
+/// Seed of an origin collection
+pub fn origin_collection_seed(origin_chain: ChainId, origin_contract_address: &str) -> [u8; 32] {
+    hashv(&[
+        &[origin_chain as u8],
+        &(origin_contract_address.len() as u32).to_be_bytes(),
+        origin_contract_address.as_bytes(),
+    ])
+    .to_bytes()
+}
+
+/// Seed of an origin NFT, the collection encoding followed by the 32 byte big-endian token id
+pub fn origin_nft_seed(
+    origin_chain: ChainId,
+    origin_contract_address: &str,
+    nft_id: &[u8; 32],
+) -> [u8; 32] {
+    hashv(&[
+        &[origin_chain as u8],
+        &(origin_contract_address.len() as u32).to_be_bytes(),
+        origin_contract_address.as_bytes(),
+        nft_id,
+    ])
+    .to_bytes()
+}
+
+/// Mint of the wrapped NFT `creator_program` mints for an origin NFT, so any client can find
+/// the Solana address of a bridged NFT from its origin identifiers
+pub fn wrapped_nft_mint(
+    origin_chain: ChainId,
+    origin_contract_address: &str,
+    nft_id: &[u8; 32],
+    creator_program: &Pubkey,
+) -> Pubkey {
+    Pubkey::find_program_address(
+        &[
+            NFT_MINT.as_bytes(),
+            &origin_nft_seed(origin_chain, origin_contract_address, nft_id),
+        ],
+        creator_program,
+    )
+    .0
+}
+
+/// Loads the collection info `creator_program` keeps for an origin collection, checking it
+/// sits at its PDA
+pub fn load_collection_info(
+    account: &AccountInfo,
+    creator_program: &Pubkey,
+    origin_chain: ChainId,
+    origin_contract_address: &str,
+) -> Result<CollectionInfo> {
+    require_keys_eq!(
+        *account.owner,
+        *creator_program,
+        BridgeError::CollectionNotAllowed
+    );
+    let (address, _) = Pubkey::find_program_address(
+        &[
+            COLLECTION_INFO.as_bytes(),
+            &origin_collection_seed(origin_chain, origin_contract_address),
+        ],
+        creator_program,
+    );
+    require_keys_eq!(account.key(), address, BridgeError::CollectionNotAllowed);
+
+    CollectionInfo::try_deserialize(&mut &account.try_borrow_data()?[..])
+}
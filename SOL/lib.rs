@@ -1,109 +1,133 @@
 use anchor_lang::prelude::*;
-use anchor_lang::solana_program::pubkey;
+use anchor_lang::solana_program::hash::hash;
 use anchor_spl::token::{Mint, Token, TokenAccount, Transfer};
 use solana_program::program::invoke;
 
+#[path = "common/chains.rs"]
+pub mod chains;
+use chains::ChainId;
+
 declare_id!("7dX8QYJfiMv62X2MtRxE2MTacBHKvHJpzVw71yiAbCtn");
 
+#[constant]
+pub const BRIDGE_CONFIG: &str = "bridge_config";
+pub const PROCESSED_TRANSFER: &str = "processed_transfer";
+pub const TREASURY: &str = "treasury";
+
 #[program]
 mod nft_bridge {
     use super::*;
 
-    pub fn initialize_bridge(_ctx: Context<InitializeBridge>) -> Result<()> {
+    pub fn initialize_bridge(
+        ctx: Context<InitializeBridge>,
+        operator: Pubkey,
+        treasury: Pubkey,
+    ) -> Result<()> {
+        let bridge_config = &mut ctx.accounts.bridge_config;
+
+        bridge_config.admin = ctx.accounts.signer.key();
+        bridge_config.operator = operator;
+        bridge_config.treasury = treasury;
+        bridge_config.bump = ctx.bumps.bridge_config;
+
+        ctx.accounts.treasury_pda.bump = ctx.bumps.treasury_pda;
+
         Ok(())
     }
-    pub fn initialize_and_lock_nft(
-        ctx: Context<InitializeAndLockNft>,
-        nft_id: u64,
-        coll_id: String,
-        src_address: String,
-        dst_chain: String,
-        dst_address: String,
-    ) -> Result<()> {
-        // Initialization logic (creating PDAs, etc.)
-        msg!("Initialization successful!");
-        // Transfer tokens from sender's token account to the bridge's NFT token account
-        let transfer_instruction = Transfer {
-            from: ctx.accounts.sender_token_account.to_account_info(),
-            to: ctx.accounts.nft_token_account.to_account_info(),
-            authority: ctx.accounts.signer.to_account_info(),
-        };
 
-        let cpi_ctx = CpiContext::new(
-            ctx.accounts.token_program.to_account_info(),
-            transfer_instruction,
+    pub fn set_admin(ctx: Context<UpdateBridgeConfig>, new_admin: Pubkey) -> Result<()> {
+        let bridge_config = &mut ctx.accounts.bridge_config;
+        msg!("Admin changed: {} -> {}", bridge_config.admin, new_admin);
+        bridge_config.admin = new_admin;
+
+        Ok(())
+    }
+
+    pub fn set_operator(ctx: Context<UpdateBridgeConfig>, new_operator: Pubkey) -> Result<()> {
+        let bridge_config = &mut ctx.accounts.bridge_config;
+        msg!(
+            "Operator changed: {} -> {}",
+            bridge_config.operator,
+            new_operator
         );
+        bridge_config.operator = new_operator;
 
-        // Perform the transfer
-        anchor_spl::token::transfer(cpi_ctx, 1)?;
+        Ok(())
+    }
 
-        msg!("NFT ID: {}", nft_id);
-        msg!("Collection ID: {}", coll_id);
-        msg!("Source Address: {}", src_address);
-        msg!("Destination Chain: {}", dst_chain);
-        msg!("Destination Address: {}", dst_address);
+    pub fn set_service_fee(ctx: Context<UpdateBridgeConfig>, service_fee: u64) -> Result<()> {
+        ctx.accounts.bridge_config.service_fee = service_fee;
 
         Ok(())
     }
 
+    pub fn withdraw_fees(ctx: Context<WithdrawFees>) -> Result<()> {
+        let treasury_pda = ctx.accounts.treasury_pda.to_account_info();
+
+        // Keep the treasury rent exempt, everything above that is collected fees
+        let rent_exempt = Rent::get()?.minimum_balance(treasury_pda.data_len());
+        let balance = treasury_pda.lamports().saturating_sub(rent_exempt);
+
+        if balance > 0 {
+            **treasury_pda.try_borrow_mut_lamports()? -= balance;
+            **ctx.accounts.treasury.try_borrow_mut_lamports()? += balance;
+        }
+
+        Ok(())
+    }
+
+    // Same as initialize_and_lock_nft_fee, charging the configured service fee
+    pub fn initialize_and_lock_nft(
+        ctx: Context<InitializeAndLockNftFee>,
+        nft_id: [u8; 32],
+        coll_id: String,
+        src_address: String,
+        dst_chain: ChainId,
+        dst_address: String,
+    ) -> Result<()> {
+        let amount = ctx.accounts.bridge_config.service_fee;
+        lock_nft(
+            ctx,
+            amount,
+            nft_id,
+            coll_id,
+            src_address,
+            dst_chain,
+            dst_address,
+        )
+    }
+
     pub fn initialize_and_lock_nft_fee(
         ctx: Context<InitializeAndLockNftFee>,
         amount: u64,
-        nft_id: u64,
+        nft_id: [u8; 32],
         coll_id: String,
         src_address: String,
-        dst_chain: String,
+        dst_chain: ChainId,
         dst_address: String,
     ) -> Result<()> {
-        // Initialization logic (creating PDAs, etc.)
-        msg!("Initialization successful!");
-
-        // Token transfer logic (moving tokens into the bridge)
-        msg!("Token amount transfer in: 1 ");
-
-        // Transfer tokens from sender's token account to the bridge's NFT token account
-        let transfer_instruction = Transfer {
-            from: ctx.accounts.sender_token_account.to_account_info(),
-            to: ctx.accounts.nft_token_account.to_account_info(),
-            authority: ctx.accounts.signer.to_account_info(),
-        };
-
-        let cpi_ctx = CpiContext::new(
-            ctx.accounts.token_program.to_account_info(),
-            transfer_instruction,
+        // The fee always goes to the treasury PDA, the caller only confirms the amount
+        require!(
+            amount == ctx.accounts.bridge_config.service_fee,
+            BridgeError::InvalidFee
         );
 
-        // Perform the transfer
-        anchor_spl::token::transfer(cpi_ctx, 1)?;
-
-        let sol_transfer = anchor_lang::solana_program::system_instruction::transfer(
-            &ctx.accounts.from_pubkey.key,
-            &ctx.accounts.to_pubkey.key,
+        lock_nft(
+            ctx,
             amount,
-        );
-        invoke(
-            &sol_transfer,
-            &[
-                ctx.accounts.from_pubkey.clone(),
-                ctx.accounts.to_pubkey.clone(),
-                ctx.accounts.system_program.to_account_info(),
-            ],
-        )?;
-
-        msg!("NFT ID: {}", nft_id);
-        msg!("Collection ID: {}", coll_id);
-        msg!("Source Address: {}", src_address);
-        msg!("Destination Chain: {}", dst_chain);
-        msg!("Destination Address: {}", dst_address);
-
-        Ok(())
+            nft_id,
+            coll_id,
+            src_address,
+            dst_chain,
+            dst_address,
+        )
     }
 
     pub fn unlock_nft(
         ctx: Context<UnlockNft>,
-        nft_id: u64,
+        nft_id: [u8; 32],
         coll_id: String,
-        src_chain: String,
+        src_chain: ChainId,
         src_address: String,
         dst_address: String,
         bridge_txid: String,
@@ -129,12 +153,23 @@ mod nft_bridge {
 
         anchor_spl::token::transfer(cpi_ctx, 1)?;
 
-        msg!("NFT ID: {}", nft_id);
-        msg!("Collection ID: {}", coll_id);
-        msg!("Source Chain: {}", src_chain);
-        msg!("Source Address: {}", src_address);
-        msg!("Destination Address: {}", dst_address);
-        msg!("Bridge TxId: {}", bridge_txid);
+        let processed_transfer = &mut ctx.accounts.processed_transfer;
+        processed_transfer.processed = true;
+        processed_transfer.mint = ctx.accounts.mint_of_token_being_sent.key();
+        processed_transfer.receiver = ctx.accounts.receiver_token_account.owner;
+        processed_transfer.processed_at = Clock::get()?.unix_timestamp;
+        processed_transfer.bump = ctx.bumps.processed_transfer;
+
+        emit!(NftUnlocked {
+            src_chain,
+            src_address,
+            receiver: ctx.accounts.receiver_token_account.owner,
+            mint: ctx.accounts.mint_of_token_being_sent.key(),
+            nft_id,
+            coll_id,
+            dst_address,
+            bridge_txid,
+        });
 
         Ok(())
     }
@@ -153,7 +188,32 @@ pub struct InitializeBridge<'info> {
     )]
     bridge_pda: AccountInfo<'info>,
 
-    #[account(mut,address=pubkey!("7QHySLfCkeSBUGxtqM3WdeKv9X4YUXMeTn3gr5fRPCGU"))]
+    #[account(
+        init,
+        payer = signer,
+        seeds=[BRIDGE_CONFIG.as_bytes()],
+        bump,
+        space = 8 + BridgeConfig::INIT_SPACE
+    )]
+    pub bridge_config: Account<'info, BridgeConfig>,
+
+    #[account(
+        init,
+        payer = signer,
+        seeds=[TREASURY.as_bytes()],
+        bump,
+        space = 8 + Treasury::INIT_SPACE
+    )]
+    pub treasury_pda: Account<'info, Treasury>,
+
+    // Only the upgrade authority of this program may create the config
+    #[account(constraint = program.programdata_address()? == Some(program_data.key()))]
+    pub program: Program<'info, crate::program::NftBridge>,
+
+    #[account(constraint = program_data.upgrade_authority_address == Some(signer.key()) @ BridgeError::Unauthorized)]
+    pub program_data: Account<'info, ProgramData>,
+
+    #[account(mut)]
     signer: Signer<'info>,
     system_program: Program<'info, System>,
     token_program: Program<'info, Token>,
@@ -161,36 +221,99 @@ pub struct InitializeBridge<'info> {
 }
 
 #[derive(Accounts)]
-pub struct InitializeAndLockNft<'info> {
-    // Derived PDAs and accounts for initialization
+pub struct UpdateBridgeConfig<'info> {
     #[account(
         mut,
-        seeds=[b"bridge"],
-        bump
+        seeds=[BRIDGE_CONFIG.as_bytes()],
+        bump = bridge_config.bump,
+        has_one = admin @ BridgeError::Unauthorized
     )]
-    pub bridge_pda: AccountInfo<'info>, // `mut` is needed because the PDA will be initialized
+    pub bridge_config: Account<'info, BridgeConfig>,
 
-    pub mint_of_token_being_sent: Account<'info, Mint>,
+    pub admin: Signer<'info>,
+}
 
+#[derive(Accounts)]
+pub struct WithdrawFees<'info> {
     #[account(
-        init_if_needed,
-        payer = signer,
-        seeds = [b"nft", mint_of_token_being_sent.key().as_ref()],
-        token::mint = mint_of_token_being_sent,
-        token::authority = bridge_pda,
-        bump
+        seeds=[BRIDGE_CONFIG.as_bytes()],
+        bump = bridge_config.bump,
+        has_one = admin @ BridgeError::Unauthorized,
+        has_one = treasury
     )]
-    pub nft_token_account: Account<'info, TokenAccount>, // `mut` is needed because the account will be initialized
+    pub bridge_config: Account<'info, BridgeConfig>,
 
-    #[account(mut)]
-    pub sender_token_account: Account<'info, TokenAccount>, // `mut` is needed because the account will be used for transfer
+    #[account(
+        mut,
+        seeds=[TREASURY.as_bytes()],
+        bump = treasury_pda.bump
+    )]
+    pub treasury_pda: Account<'info, Treasury>,
 
+    /// CHECK: fee recipient stored in the bridge config
     #[account(mut)]
-    pub signer: Signer<'info>, // `mut` is needed because the signer is part of the transaction
+    pub treasury: UncheckedAccount<'info>,
 
-    pub system_program: Program<'info, System>,
-    pub token_program: Program<'info, Token>,
-    pub rent: Sysvar<'info, Rent>,
+    pub admin: Signer<'info>,
+}
+
+/// Moves the NFT into custody and charges `amount` into the treasury PDA
+fn lock_nft(
+    ctx: Context<InitializeAndLockNftFee>,
+    amount: u64,
+    nft_id: [u8; 32],
+    coll_id: String,
+    src_address: String,
+    dst_chain: ChainId,
+    dst_address: String,
+) -> Result<()> {
+    // Initialization logic (creating PDAs, etc.)
+    msg!("Initialization successful!");
+
+    // Token transfer logic (moving tokens into the bridge)
+    msg!("Token amount transfer in: 1 ");
+
+    // Transfer tokens from sender's token account to the bridge's NFT token account
+    let transfer_instruction = Transfer {
+        from: ctx.accounts.sender_token_account.to_account_info(),
+        to: ctx.accounts.nft_token_account.to_account_info(),
+        authority: ctx.accounts.signer.to_account_info(),
+    };
+
+    let cpi_ctx = CpiContext::new(
+        ctx.accounts.token_program.to_account_info(),
+        transfer_instruction,
+    );
+
+    // Perform the transfer
+    anchor_spl::token::transfer(cpi_ctx, 1)?;
+
+    let sol_transfer = anchor_lang::solana_program::system_instruction::transfer(
+        &ctx.accounts.signer.key(),
+        &ctx.accounts.treasury_pda.key(),
+        amount,
+    );
+    invoke(
+        &sol_transfer,
+        &[
+            ctx.accounts.signer.to_account_info(),
+            ctx.accounts.treasury_pda.to_account_info(),
+            ctx.accounts.system_program.to_account_info(),
+        ],
+    )?;
+
+    emit!(NftLocked {
+        owner: ctx.accounts.signer.key(),
+        mint: ctx.accounts.mint_of_token_being_sent.key(),
+        nft_id,
+        coll_id,
+        src_address,
+        dst_chain,
+        dst_address,
+        fee: amount,
+    });
+
+    Ok(())
 }
 
 #[derive(Accounts)]
@@ -215,14 +338,23 @@ pub struct InitializeAndLockNftFee<'info> {
     )]
     pub nft_token_account: Account<'info, TokenAccount>, // `mut` is needed because the account will be initialized
 
-    #[account(mut)]
+    #[account(
+        mut,
+        constraint = sender_token_account.owner == signer.key()
+            && sender_token_account.mint == mint_of_token_being_sent.key()
+            && sender_token_account.amount == 1 @ BridgeError::InvalidNftOwner
+    )]
     pub sender_token_account: Account<'info, TokenAccount>, // `mut` is needed because the account will be used for transfer
 
-    #[account(mut)]
-    pub to_pubkey: AccountInfo<'info>,
+    #[account(seeds=[BRIDGE_CONFIG.as_bytes()], bump = bridge_config.bump)]
+    pub bridge_config: Account<'info, BridgeConfig>,
 
-    #[account(mut)]
-    pub from_pubkey: AccountInfo<'info>,
+    #[account(
+        mut,
+        seeds=[TREASURY.as_bytes()],
+        bump = treasury_pda.bump
+    )]
+    pub treasury_pda: Account<'info, Treasury>,
 
     #[account(mut)]
     pub signer: Signer<'info>, // `mut` is needed because the signer is part of the transaction
@@ -233,6 +365,12 @@ pub struct InitializeAndLockNftFee<'info> {
 }
 
 #[derive(Accounts)]
+#[instruction(nft_id: [u8; 32],
+        coll_id: String,
+        src_chain: ChainId,
+        src_address: String,
+        dst_address: String,
+        bridge_txid: String,)]
 pub struct UnlockNft<'info> {
     // Derived PDAs
     #[account(
@@ -256,10 +394,111 @@ pub struct UnlockNft<'info> {
 
     mint_of_token_being_sent: Account<'info, Mint>,
 
-    //Change it to the address you want
-    #[account(mut,address=pubkey!("7QHySLfCkeSBUGxtqM3WdeKv9X4YUXMeTn3gr5fRPCGU"))]
+    // One per inbound transfer, a second unlock with the same id is rejected
+    #[account(
+        init_if_needed,
+        payer = signer,
+        seeds=[PROCESSED_TRANSFER.as_bytes(), &[src_chain as u8], &hash(bridge_txid.as_bytes()).to_bytes()],
+        bump,
+        space = 8 + ProcessedTransfer::INIT_SPACE,
+        constraint = !processed_transfer.processed @ BridgeError::TransferAlreadyProcessed
+    )]
+    pub processed_transfer: Account<'info, ProcessedTransfer>,
+
+    #[account(seeds=[BRIDGE_CONFIG.as_bytes()], bump = bridge_config.bump)]
+    pub bridge_config: Account<'info, BridgeConfig>,
+
+    #[account(mut, address = bridge_config.operator @ BridgeError::Unauthorized)]
     signer: Signer<'info>,
     system_program: Program<'info, System>,
     token_program: Program<'info, Token>,
     rent: Sysvar<'info, Rent>,
 }
+
+#[account]
+#[derive(InitSpace)]
+pub struct BridgeConfig {
+    pub admin: Pubkey,
+    pub operator: Pubkey,
+    pub treasury: Pubkey,
+    pub service_fee: u64,
+    pub bump: u8,
+}
+
+/// Holds collected bridge fees until they are withdrawn to `BridgeConfig.treasury`
+#[account]
+#[derive(InitSpace)]
+pub struct Treasury {
+    pub bump: u8,
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct ProcessedTransfer {
+    pub processed: bool,
+    pub mint: Pubkey,
+    pub receiver: Pubkey,
+    pub processed_at: i64,
+    pub bump: u8,
+}
+
+/// Mirrors the EVM `NFTLocked` event
+#[event]
+pub struct NftLocked {
+    pub owner: Pubkey,
+    pub mint: Pubkey,
+    // Big-endian, so an EVM uint256 token id maps over unchanged
+    pub nft_id: [u8; 32],
+    pub coll_id: String,
+    pub src_address: String,
+    pub dst_chain: ChainId,
+    pub dst_address: String,
+    pub fee: u64,
+}
+
+/// Mirrors the EVM `NFTUnlocked` event
+#[event]
+pub struct NftUnlocked {
+    pub src_chain: ChainId,
+    pub src_address: String,
+    pub receiver: Pubkey,
+    pub mint: Pubkey,
+    pub nft_id: [u8; 32],
+    pub coll_id: String,
+    pub dst_address: String,
+    pub bridge_txid: String,
+}
+
+/// Same codes as the `BridgeError` of the bridge program (`SOL/bridge`)
+#[error_code]
+pub enum BridgeError {
+    #[msg("Chain is not registered or has been disabled")]
+    ChainNotAllowed,
+    #[msg("Collection is not mapped to the destination chain")]
+    CollectionNotAllowed,
+    #[msg("Attestation is not signed by the bridge operator")]
+    SenderNotBridgeManager,
+    #[msg("Fee does not match the configured bridge fee")]
+    InvalidFee,
+    // Fees are never refunded on Solana, kept so the codes line up with EVM
+    #[msg("Fee refund failed")]
+    RefundFailed,
+    #[msg("Sender does not hold the NFT")]
+    InvalidNftOwner,
+    #[msg("Number of NFTs is zero or above the batch limit")]
+    InvalidNftLength,
+    #[msg("Transfer with this bridge txid has already been processed")]
+    TransferAlreadyProcessed,
+    #[msg("Bridge is paused")]
+    BridgePaused,
+    #[msg("Destination address is not valid for the destination chain")]
+    InvalidDestinationAddress,
+    #[msg("Signer is not authorized to perform this action")]
+    Unauthorized,
+    #[msg("Guardian set is invalid")]
+    InvalidGuardianSet,
+    #[msg("Guardian set has expired")]
+    GuardianSetExpired,
+    #[msg("Guardian signatures are missing or below the threshold")]
+    InvalidGuardianSignature,
+}